@@ -0,0 +1,91 @@
+//!Workloads in the style of `tst2.lox`,to compare the two ways values are stored in scopes.
+//!
+//!    cargo bench --bench values
+//!    cargo bench --bench values --features nan-boxing
+use std::{
+    io::sink,
+    mem::size_of,
+    time::{Duration, Instant},
+};
+
+use rlox::{
+    interpreter::{environment::Slot, Interpreter},
+    lox_object::Values,
+    lox_runner::code_to_stblock,
+};
+
+const RUNS: usize = 7;
+const WORKLOADS: [(&str, &str); 4] = [
+    (
+        "summation",
+        "fun summation(a,b){
+            var sum=0;
+            for(var i=a;i<=b;i=i+1){
+                sum=sum+i;
+            }
+            return sum;
+        }
+        summation(0,200000);",
+    ),
+    (
+        "fibbonaci",
+        "fun fibbonaci(n){
+            if(n==0 or n==1){return n;}
+            else{return fibbonaci(n-1)+fibbonaci(n-2);}
+        }
+        fibbonaci(20);",
+    ),
+    (
+        "strings",
+        "var text=\"\";
+        for(var i=0;i<20000;i=i+1){
+            var word=\"ab\";
+            if(word==\"ab\"){text=word+i;}
+        }",
+    ),
+    (
+        "closures",
+        "fun counter(){
+            var count=0;
+            fun next(){count=count+1;return count;}
+            return next;
+        }
+        for(var i=0;i<20000;i=i+1){
+            var next=counter();
+            next();
+        }",
+    ),
+];
+
+fn run(code: &str) -> Duration {
+    let mut stdout = sink();
+    let ast = code_to_stblock(code).unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    let start = Instant::now();
+    interpreter.try_interpret(&ast).unwrap();
+    start.elapsed()
+}
+
+fn main() {
+    let representation = if cfg!(feature = "nan-boxing") {
+        "nan-boxed"
+    } else {
+        "enum"
+    };
+    println!(
+        "representation:{} slot:{} bytes,Values:{} bytes",
+        representation,
+        size_of::<Slot>(),
+        size_of::<Values>()
+    );
+    for (name, code) in WORKLOADS {
+        let mut times: Vec<_> = (0..RUNS).map(|_| run(code)).collect();
+        times.sort();
+        println!(
+            "{:<10} min {:>10.3?} median {:>10.3?}",
+            name,
+            times[0],
+            times[RUNS / 2]
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::interner::Symbol;
 use crate::interpreter::environment::Environment;
 use crate::lox_object::Values;
 use crate::token::{Token, TokenType};
@@ -12,6 +13,39 @@ pub enum ExprMetaData<'tok> {
     None,
     Var { token: Token<'tok> },
 }
+///A borrowed look at what an expression is made of,for the tools that walk the tree instead of
+///running it.
+pub enum ExprView<'s, 'a> {
+    Binary {
+        left: &'s (dyn Expr<'a> + 'a),
+        operator: Token<'a>,
+        right: &'s (dyn Expr<'a> + 'a),
+    },
+    Logical {
+        left: &'s (dyn Expr<'a> + 'a),
+        operator: Token<'a>,
+        right: &'s (dyn Expr<'a> + 'a),
+    },
+    Unary {
+        operator: Token<'a>,
+        right: &'s (dyn Expr<'a> + 'a),
+    },
+    Grouping(&'s (dyn Expr<'a> + 'a)),
+    Literal(Token<'a>),
+    ///A value put in the tree by the parser,like the `nil` of `var a;`,or by folding constants.
+    ///It has no token.
+    Value(&'s Values<'a>),
+    Variable(Token<'a>),
+    Assign {
+        name: Token<'a>,
+        value: &'s (dyn Expr<'a> + 'a),
+    },
+    Call {
+        callee: &'s (dyn Expr<'a> + 'a),
+        paren: Token<'a>,
+        arguments: &'s [DynExpr<'a>],
+    },
+}
 pub trait Expr<'tok>: Debug {
 //    fn evaluate_to_obj(&self, env: &mut Environment<'tok>) -> Result<Object<'tok>, String>;
     fn evaluate_to_val(&self, env: &mut Environment<'tok>) -> Result<Values<'tok>, Box<dyn Error>> ;
@@ -20,6 +54,32 @@ pub trait Expr<'tok>: Debug {
     fn metadata(&self) -> ExprMetaData<'tok> {
         ExprMetaData::None
     }
+    fn view(&self) -> ExprView<'_, 'tok>;
+    ///The expression with its constant parts computed ahead,giving the same value when run.
+    fn optimize(self: Box<Self>) -> DynExpr<'tok>;
+    ///Line where the expression starts,`None` for a value the parser put in.
+    fn line(&self) -> Option<usize> {
+        match self.view() {
+            ExprView::Binary { left, operator, .. } | ExprView::Logical { left, operator, .. } => {
+                left.line().or(Some(operator.line()))
+            }
+            ExprView::Unary { operator, .. } => Some(operator.line()),
+            ExprView::Grouping(x) => x.line(),
+            ExprView::Literal(x) | ExprView::Variable(x) | ExprView::Assign { name: x, .. } => {
+                Some(x.line())
+            }
+            ExprView::Value(_) => None,
+            ExprView::Call { callee, paren, .. } => callee.line().or(Some(paren.line())),
+        }
+    }
+}
+///The value of `expression` if it is a constant.
+pub(crate) fn constant<'a>(expression: &(dyn Expr<'a> + 'a)) -> Option<Values<'a>> {
+    match expression.view() {
+        ExprView::Value(x) => Some(x.clone()),
+        ExprView::Literal(x) => Literal::new(x).value,
+        _ => None,
+    }
 }
 #[derive(Debug)]
 pub struct BinaryOp<'a> {
@@ -42,6 +102,13 @@ impl<'a> CallExpr<'a> {
             arguments,
         }
     }
+    ///Line where the call starts,the closing parenthesis can be lines later.
+    pub fn line(&self) -> usize {
+        match self.callee.metadata() {
+            ExprMetaData::Var { token } => token.line(),
+            ExprMetaData::None => self.paren.line(),
+        }
+    }
 }
 impl<'a> Expr<'a> for CallExpr<'a> {
     fn evaluate_to_val(&self, env: &mut Environment<'a>) -> Result<Values<'a>, Box<dyn Error>> {
@@ -55,15 +122,36 @@ impl<'a> Expr<'a> for CallExpr<'a> {
             return Err("Can only call function and classes..".into());
         };
         if !function.arity(arguments.len()){
-            return Err("Function have different arguments.".into());
+            return Err(format!("Function {} have different arguments.", function.name()).into());
         }
-        return function.call(env, &arguments);
+        env.step()?;
+        env.budget_mut().enter()?;
+        env.set_call_line(self.line());
+        let result = function.call(env, &arguments);
+        env.budget_mut().leave();
+        result
+    }
+    fn view(&self) -> ExprView<'_, 'a> {
+        ExprView::Call {
+            callee: self.callee.as_ref(),
+            paren: self.paren,
+            arguments: &self.arguments,
+        }
+    }
+    fn optimize(self: Box<Self>) -> DynExpr<'a> {
+        let arguments = self.arguments.into_vec().into_iter();
+        Box::new(Self {
+            callee: self.callee.optimize(),
+            paren: self.paren,
+            arguments: arguments.map(|x| x.optimize()).collect(),
+        })
     }
 }
 
 #[derive(Debug)]
 pub struct Variable<'a> {
     name: Token<'a>,
+    symbol: Symbol,
 }
 #[derive(Debug)]
 pub struct Logical<'a> {
@@ -85,14 +173,41 @@ impl<'a> Expr<'a> for Logical<'a> {
     fn evaluate_to_val(&self, env: &mut Environment<'a>) -> Result<Values<'a>,Box<dyn Error>> {
         let left = self.left.evaluate_to_val(env)?;
         let ans = match (self.operator.get_type(), left.is_truthy()) {
-            (TokenType::Or, true) => left,
-            (TokenType::And, false) => left,
-            (x, _y) if matches!(x, TokenType::Or | TokenType::And) => {
+            (TokenType::Or, true) | (TokenType::And, false) => {
+                env.branch(self.operator, 0);
+                left
+            }
+            (TokenType::Or | TokenType::And, _) => {
+                env.branch(self.operator, 1);
                 self.right.evaluate_to_val(env)?
             }
             _ => panic!("There shoudn't be other toekn."),
         };
-        return Ok(ans);
+        Ok(ans)
+    }
+    fn view(&self) -> ExprView<'_, 'a> {
+        ExprView::Logical {
+            left: self.left.as_ref(),
+            operator: self.operator,
+            right: self.right.as_ref(),
+        }
+    }
+    fn optimize(self: Box<Self>) -> DynExpr<'a> {
+        let line = self.line();
+        let left = self.left.optimize();
+        let right = self.right.optimize();
+        let Some(value) = constant(left.as_ref()) else {
+            return Box::new(Self { left, operator: self.operator, right });
+        };
+        //A constant left side decides if the right side runs.
+        let kept = match (self.operator.get_type(), value.is_truthy()) {
+            (TokenType::Or, true) | (TokenType::And, false) => left,
+            _ => right,
+        };
+        match constant(kept.as_ref()) {
+            Some(x) => folded(x, line),
+            None => kept,
+        }
     }
 }
 
@@ -103,7 +218,7 @@ impl Display for Variable<'_> {
 }
 impl<'a> Expr<'a> for Variable<'a> {
     fn evaluate_to_val(&self, env: &mut Environment<'a>) -> Result<Values<'a>,Box<dyn Error>> {
-        match env.get(self.name.as_str()){
+        match env.get(self.symbol){
             Some(x)=>Ok(x),
             None=>{
                 let mut err=self.name.to_string();
@@ -115,44 +230,74 @@ impl<'a> Expr<'a> for Variable<'a> {
     fn metadata(&self) -> ExprMetaData<'a> {
         ExprMetaData::Var { token: self.name }
     }
+    fn view(&self) -> ExprView<'_, 'a> {
+        ExprView::Variable(self.name)
+    }
+    fn optimize(self: Box<Self>) -> DynExpr<'a> {
+        self
+    }
 }
 impl<'a> Variable<'a> {
     pub fn new(name: Token<'a>) -> Self {
-        Variable { name }
+        let symbol = Symbol::intern(name.as_str());
+        Variable { name, symbol }
     }
 }
 #[derive(Debug)]
 pub struct Assign<'b> {
     name: Token<'b>,
+    symbol: Symbol,
     value: DynExpr<'b>,
 }
 
 impl<'b> Assign<'b> {
     pub fn new(name: Token<'b>, value: DynExpr<'b>) -> Self {
-        Self { name, value }
+        let symbol = Symbol::intern(name.as_str());
+        Self { name, symbol, value }
+    }
+    pub fn name(&self) -> Token<'b> {
+        self.name
     }
 }
 impl<'b> Expr<'b> for Assign<'b> {
     fn evaluate_to_val(&self, env: &mut Environment<'b>) -> Result<Values<'b>, Box<dyn Error>> {
         let value = self.value.evaluate_to_val(env)?;
-        env.redefine(self.name.as_str(), value)?;
+        env.redefine(self.symbol, value)?;
         Ok(Values::Null)
     }
+    fn view(&self) -> ExprView<'_, 'b> {
+        ExprView::Assign {
+            name: self.name,
+            value: self.value.as_ref(),
+        }
+    }
+    fn optimize(self: Box<Self>) -> DynExpr<'b> {
+        Box::new(Self {
+            value: self.value.optimize(),
+            ..*self
+        })
+    }
 }
 
+///A value and the line of the code it was computed from,if any.
 #[derive(Debug)]
-pub struct ValueStmt<'a>(Values<'a>);
+pub struct ValueStmt<'a>(Values<'a>, Option<usize>);
 impl<'a> From<Values<'a>> for Box<dyn Expr<'a>+'a> {
     fn from(value:Values<'a>) -> Self {
-        Box::new(ValueStmt(value))
+        Box::new(ValueStmt(value, None))
     }
 }
 
 impl<'a> From<Values<'a>> for ValueStmt<'a> {
     fn from(value: Values<'a>) -> Self {
-        ValueStmt(value)
+        ValueStmt(value, None)
     }
 }
+///The value an expression starting on `line` folded to.It keeps the line,so the statement
+///hooks see the same statements with or without the optimizer.
+fn folded<'a>(value: Values<'a>, line: Option<usize>) -> DynExpr<'a> {
+    Box::new(ValueStmt(value, line))
+}
 
 impl Display for ValueStmt<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -163,6 +308,15 @@ impl<'a> Expr<'a> for ValueStmt<'a> {
     fn evaluate_to_val(&self, _env: &mut Environment) -> Result<Values<'a>,Box<dyn Error>> {
         Ok(self.0.clone())
     }
+    fn view(&self) -> ExprView<'_, 'a> {
+        ExprView::Value(&self.0)
+    }
+    fn optimize(self: Box<Self>) -> DynExpr<'a> {
+        self
+    }
+    fn line(&self) -> Option<usize> {
+        self.1
+    }
 }
 impl<'a> BinaryOp<'a> {
     pub fn new(left: DynExpr<'a>, operator: Token<'a>, right: DynExpr<'a>) -> Self {
@@ -186,14 +340,25 @@ impl<'a> Grouping<'a> {
 #[derive(Debug, Clone)]
 pub struct Literal<'a> {
     token: Token<'a>,
+    value: Option<Values<'a>>,
 }
 
 impl<'a> Literal<'a> {
+    ///The value is made here,at parse time,so running the literal only clones it.
     pub fn new(token: Token<'a>) -> Self {
-        Self { token }
+        use TokenType::{False, Nil, Number, String, True};
+        let value = match token.get_type() {
+            String => Some(Values::Str(token.as_str().into())),
+            Number => Some(Values::Number(token.as_str().parse().unwrap())),
+            True => Some(Values::Boolean(1.0)),
+            False => Some(Values::Boolean(0.0)),
+            Nil => Some(Values::Null),
+            _ => None,
+        };
+        Self { token, value }
     }
-    fn token_type(&self) -> TokenType {
-        self.token.get_type()
+    pub fn token(&self) -> Token<'a> {
+        self.token
     }
 }
 #[derive(Debug)]
@@ -207,14 +372,13 @@ impl<'a> Unary<'a> {
         Self { operator, right }
     }
 }
-impl<'a> Expr<'a> for BinaryOp<'a> {
-    fn evaluate_to_val(&self, env: &mut Environment<'a>) -> Result<Values<'a>,Box<dyn Error>> {
-        let left = self.left.evaluate_to_val(env)?;
-        let right = self.right.evaluate_to_val(env)?;
+impl<'a> BinaryOp<'a> {
+    ///The operator applied to two values,when running and when folding constants.
+    fn apply(&self, left: Values<'a>, right: Values<'a>) -> Result<Values<'a>, Box<dyn Error>> {
         use TokenType::{
             BangEqual, EqualEqual, Greater, GreaterEqual, Less, LessEqual, Minus, Plus, Slash, Star,
         };
-        let ans=match self.operator.get_type() {
+        let ans = match self.operator.get_type() {
             Minus => left.sub(right),
             Plus => left.add(right),
             Star => left.mul(right),
@@ -230,29 +394,67 @@ impl<'a> Expr<'a> for BinaryOp<'a> {
         Ok(ans)
     }
 }
+impl<'a> Expr<'a> for BinaryOp<'a> {
+    fn evaluate_to_val(&self, env: &mut Environment<'a>) -> Result<Values<'a>,Box<dyn Error>> {
+        let left = self.left.evaluate_to_val(env)?;
+        let right = self.right.evaluate_to_val(env)?;
+        let ans = self.apply(left, right)?;
+        env.heap_mut().allocate(ans.heap_size())?;
+        Ok(ans)
+    }
+    fn view(&self) -> ExprView<'_, 'a> {
+        ExprView::Binary {
+            left: self.left.as_ref(),
+            operator: self.operator,
+            right: self.right.as_ref(),
+        }
+    }
+    fn optimize(self: Box<Self>) -> DynExpr<'a> {
+        let line = self.line();
+        let optimized = Self {
+            left: self.left.optimize(),
+            operator: self.operator,
+            right: self.right.optimize(),
+        };
+        let left = constant(optimized.left.as_ref());
+        let right = constant(optimized.right.as_ref());
+        //Errors like a division by zero are left for the run to report.
+        match left.zip(right).map(|(x, y)| optimized.apply(x, y)) {
+            Some(Ok(x)) => folded(x, line),
+            _ => Box::new(optimized),
+        }
+    }
+}
 impl<'a> Expr<'a> for Grouping<'a> {
     fn evaluate_to_val(&self, env: &mut Environment<'a>) -> Result<Values<'a>,Box<dyn Error>> {
         self.expression.evaluate_to_val(env)
     }
+    fn view(&self) -> ExprView<'_, 'a> {
+        ExprView::Grouping(self.expression.as_ref())
+    }
+    ///Parentheses only matter to the parser.
+    fn optimize(self: Box<Self>) -> DynExpr<'a> {
+        self.expression.optimize()
+    }
 }
-impl<'a> Expr<'a> for Literal<'_> {
-    fn evaluate_to_val(&self, _env: &mut Environment) -> Result<Values<'a>,Box<dyn Error>> {
-        use TokenType::{False, Number, String, True};
-        let ans = match self.token_type() {
-            String => Values::Str(self.token.to_string()),
-            Number => Values::Number(self.token.as_str().parse().unwrap()),
-            True => Values::Boolean(1.0),
-            False => Values::Boolean(0.0),
-            _ => return Err("Unexpected value,wanted boolean,number or string".into()),
-        };
-        Ok(ans)
+impl<'a> Expr<'a> for Literal<'a> {
+    fn evaluate_to_val(&self, _env: &mut Environment<'a>) -> Result<Values<'a>,Box<dyn Error>> {
+        match &self.value {
+            Some(x) => Ok(x.clone()),
+            None => Err("Unexpected value,wanted boolean,number or string".into()),
+        }
+    }
+    fn view(&self) -> ExprView<'_, 'a> {
+        ExprView::Literal(self.token)
+    }
+    fn optimize(self: Box<Self>) -> DynExpr<'a> {
+        self
     }
 }
-impl<'a> Expr<'a> for Unary<'a> {
-    fn evaluate_to_val(&self, env: &mut Environment<'a>) -> Result<Values<'a>,Box<dyn Error>> {
-        let right:Values = self.right.evaluate_to_val(env)?;
+impl<'a> Unary<'a> {
+    fn apply(&self, right: Values<'a>) -> Result<Values<'a>, Box<dyn Error>> {
         use TokenType::*;
-        let ans=match self.operator.get_type() {
+        let ans = match self.operator.get_type() {
             Minus => right.negative(),
             Bang => right.cast_to_boolean().not(),
             _ => Err("Other operator is not allowed".into()),
@@ -260,4 +462,26 @@ impl<'a> Expr<'a> for Unary<'a> {
         Ok(ans)
     }
 }
-
+impl<'a> Expr<'a> for Unary<'a> {
+    fn evaluate_to_val(&self, env: &mut Environment<'a>) -> Result<Values<'a>,Box<dyn Error>> {
+        let right:Values = self.right.evaluate_to_val(env)?;
+        self.apply(right)
+    }
+    fn view(&self) -> ExprView<'_, 'a> {
+        ExprView::Unary {
+            operator: self.operator,
+            right: self.right.as_ref(),
+        }
+    }
+    fn optimize(self: Box<Self>) -> DynExpr<'a> {
+        let line = self.line();
+        let optimized = Self {
+            operator: self.operator,
+            right: self.right.optimize(),
+        };
+        match constant(optimized.right.as_ref()).map(|x| optimized.apply(x)) {
+            Some(Ok(x)) => folded(x, line),
+            _ => Box::new(optimized),
+        }
+    }
+}
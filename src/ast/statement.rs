@@ -13,7 +13,8 @@
 ///logic_or → logic_and ( "or" logic_and )* ;
 ///logic_and → equality ( "and" equality )* ;
 use crate::{
-    ast::expression::Expr,
+    ast::expression::{constant, Expr},
+    interner::Symbol,
     interpreter::environment::Environment,
     lox_object::{LoxFunction, Values},
     token::Token,
@@ -23,6 +24,7 @@ use std::{error::Error, fmt::Debug, rc::Rc};
 use super::expression::DynExpr;
 #[derive(Debug)]
 pub struct If<'a> {
+    keyword: Token<'a>,
     condition: DynExpr<'a>,
     then_b: DynStmt<'a>,
     else_b: Option<DynStmt<'a>>,
@@ -30,27 +32,68 @@ pub struct If<'a> {
 #[derive(Debug)]
 pub struct FunctionDelc<'a> {
     name: Token<'a>,
+    symbol: Symbol,
     paran: Box<[Token<'a>]>,
-    body: RcStmt<'a>,
+    paran_symbols: Rc<[Symbol]>,
+    body: Rc<Block<'a>>,
+    ///The `///` comments in front of the declaration.
+    doc: Option<String>,
 }
 
 impl<'a> FunctionDelc<'a> {
-    pub fn new(name: Token<'a>, paran: Box<[Token<'a>]>, body: DynStmt<'a>) -> Self {
-        Self { name, paran, body:body.into() }
+    pub fn new(name: Token<'a>, paran: Box<[Token<'a>]>, body: Block<'a>, doc: Option<String>) -> Self {
+        let symbol = Symbol::intern(name.as_str());
+        let paran_symbols = paran.iter().map(|x| Symbol::intern(x.as_str())).collect();
+        Self { name, symbol, paran, paran_symbols, body:body.into(), doc }
+    }
+    pub fn name(&self) -> Token<'a> {
+        self.name
+    }
+    pub fn parameters(&self) -> &[Token<'a>] {
+        &self.paran
+    }
+    pub fn doc(&self) -> Option<&str> {
+        self.doc.as_deref()
     }
 }
 impl<'a> Stmt<'a> for FunctionDelc<'a> {
     fn execute(&self, env: &mut Environment<'a>) -> Result<Option<Values<'a>>, Box<dyn Error>> {
-        let func = LoxFunction::new(self.name, self.paran.clone(), self.body.clone(), env.get_current());
-        env.define(self.name.to_string(), Values::Fn(Rc::new(func)));
+        let func = LoxFunction::new(self.name, self.paran_symbols.clone(), self.body.clone() as RcStmt<'a>, env.get_current());
+        let func = Rc::new(func);
+        env.heap_mut().track_function(&func);
+        env.define(self.symbol, Values::Fn(func));
         Ok(None)
         //env.define(self.function.name().to_string(),Values::Fn(self.function.clone()));
         //Ok(None)
     }
+    fn metadata(&self) -> StmtMetaData<'a> {
+        StmtMetaData::Function { name: self.name }
+    }
+    fn view(&self) -> StmtView<'_, 'a> {
+        StmtView::Function {
+            name: self.name,
+            parameters: &self.paran,
+            body: self.body.as_ref(),
+            doc: self.doc.as_deref(),
+        }
+    }
+    fn optimize(mut self: Box<Self>) -> DynStmt<'a> {
+        //The body is only shared once the function has been declared.
+        if let Some(body) = Rc::get_mut(&mut self.body) {
+            *body = std::mem::take(body).optimized();
+        }
+        self
+    }
 }
 impl<'a> If<'a> {
-    pub fn new(condition: DynExpr<'a>, then_b: DynStmt<'a>, else_b: Option<DynStmt<'a>>) -> Self {
+    pub fn new(
+        keyword: Token<'a>,
+        condition: DynExpr<'a>,
+        then_b: DynStmt<'a>,
+        else_b: Option<DynStmt<'a>>,
+    ) -> Self {
         Self {
+            keyword,
             condition,
             then_b,
             else_b,
@@ -69,57 +112,183 @@ impl<'a> From<DynExpr<'a>> for ReturnStmt<'a> {
 impl<'a> Stmt<'a> for ReturnStmt<'a> {
     fn execute(&self, env: &mut Environment<'a>) -> Result<Option<Values<'a>>, Box<dyn Error>> {
         let value = self.expr.evaluate_to_val(env)?;
-        return Ok(Some(value));
+        Ok(Some(value))
+    }
+    fn view(&self) -> StmtView<'_, 'a> {
+        StmtView::Return(self.expr.as_ref())
+    }
+    fn optimize(self: Box<Self>) -> DynStmt<'a> {
+        Box::new(Self::from(self.expr.optimize()))
     }
 }
 impl<'a> Stmt<'a> for If<'a> {
     fn execute(&self, env: &mut Environment<'a>) -> Result<Option<Values<'a>>, Box<dyn Error>> {
-        let condition = self.condition.evaluate_to_val(env)?;
-        if condition.is_truthy() {
-            let ans = self.then_b.execute(env)?;
+        let condition = self.condition.evaluate_to_val(env)?.is_truthy();
+        env.branch(self.keyword, if condition { 0 } else { 1 });
+        if condition {
+            let ans = execute(self.then_b.as_ref(), env)?;
             if let Some(x) = ans {
                 return Ok(Some(x));
             }
         } else if let Some(x) = self.else_b.as_ref() {
-            if let Some(x) = x.execute(env)? {
+            if let Some(x) = execute(x.as_ref(), env)? {
                 return Ok(Some(x));
             }
         }
         Ok(None)
     }
+    fn view(&self) -> StmtView<'_, 'a> {
+        StmtView::If {
+            keyword: self.keyword,
+            condition: self.condition.as_ref(),
+            then_branch: self.then_b.as_ref(),
+            else_branch: self.else_b.as_deref(),
+        }
+    }
+    ///A constant condition leaves only the branch it picks.
+    fn optimize(self: Box<Self>) -> DynStmt<'a> {
+        let condition = self.condition.optimize();
+        let then_b = self.then_b.optimize();
+        let else_b = self.else_b.map(|x| x.optimize());
+        match constant(condition.as_ref()).map(|x| x.is_truthy()) {
+            Some(true) => then_b,
+            Some(false) => else_b.unwrap_or_else(|| Box::new(Statements::default())),
+            None => Box::new(Self::new(self.keyword, condition, then_b, else_b)),
+        }
+    }
 }
 #[derive(Debug)]
 pub struct WhileStmt<'a> {
+    ///The `while`,or the `for` the loop was written with.
+    keyword: Token<'a>,
     condition: DynExpr<'a>,
     body: DynStmt<'a>,
 }
 
 impl<'a> Stmt<'a> for WhileStmt<'a> {
     fn execute(&self, env: &mut Environment<'a>) -> Result<Option<Values<'a>>, Box<dyn Error>> {
-        while self.condition.evaluate_to_val(env)?.is_truthy() {
-            if let Some(x) = self.body.execute(env)? {
+        loop {
+            let condition = self.condition.evaluate_to_val(env)?.is_truthy();
+            env.branch(self.keyword, if condition { 0 } else { 1 });
+            if !condition {
+                break;
+            }
+            env.step()?;
+            if let Some(x) = execute(self.body.as_ref(), env)? {
                 return Ok(Some(x));
             }
         }
         Ok(None)
     }
+    fn view(&self) -> StmtView<'_, 'a> {
+        StmtView::While {
+            keyword: self.keyword,
+            condition: self.condition.as_ref(),
+            body: self.body.as_ref(),
+        }
+    }
+    fn optimize(self: Box<Self>) -> DynStmt<'a> {
+        let condition = self.condition.optimize();
+        if constant(condition.as_ref()).is_some_and(|x| !x.is_truthy()) {
+            return Box::new(Statements::default());
+        }
+        Box::new(Self::new(self.keyword, condition, self.body.optimize()))
+    }
 }
 
 impl<'a> WhileStmt<'a> {
-    pub fn new(condition: DynExpr<'a>, body: DynStmt<'a>) -> Self {
-        Self { condition, body }
+    pub fn new(keyword: Token<'a>, condition: DynExpr<'a>, body: DynStmt<'a>) -> Self {
+        Self {
+            keyword,
+            condition,
+            body,
+        }
     }
 }
 pub type DynStmt<'a> = Box<dyn Stmt<'a> + 'a>;
 pub type RcStmt<'a> = Rc<dyn Stmt<'a> + 'a>;
 pub trait Stmt<'a>: Debug {
     fn execute(&self, env: &mut Environment<'a>) -> Result<Option<Values<'a>>, Box<dyn Error>>;
+    fn metadata(&self) -> StmtMetaData<'a> {
+        StmtMetaData::None
+    }
+    fn view(&self) -> StmtView<'_, 'a>;
+    ///The statement with its constant expressions folded and the branches it can never take
+    ///removed.
+    fn optimize(self: Box<Self>) -> DynStmt<'a>;
+    ///Line the statement starts on.`None` for blocks,their statements have their own lines.
+    fn line(&self) -> Option<usize> {
+        match self.view() {
+            StmtView::Expression(x) | StmtView::Return(x) => x.line(),
+            StmtView::Var { name, .. }
+            | StmtView::Function { name, .. }
+            | StmtView::If { keyword: name, .. }
+            | StmtView::While { keyword: name, .. } => Some(name.line()),
+            StmtView::Block(_) | StmtView::Statements(_) => None,
+        }
+    }
+}
+///Run `statement` inside another one,after letting the statement hook of `env` see it.
+pub fn execute<'a>(
+    statement: &(dyn Stmt<'a> + 'a),
+    env: &mut Environment<'a>,
+) -> Result<Option<Values<'a>>, Box<dyn Error>> {
+    env.before_statement(statement)?;
+    statement.execute(env)
+}
+///Optimize every statement of `source`.
+fn optimize_all<'a>(source: Box<[DynStmt<'a>]>) -> Box<[DynStmt<'a>]> {
+    source.into_vec().into_iter().map(|x| x.optimize()).collect()
+}
+pub enum StmtMetaData<'tok> {
+    None,
+    Function { name: Token<'tok> },
+}
+///A borrowed look at what a statement is made of,see `ExprView`.
+///A `for` loop is already a block holding its initializer and a `while`.
+pub enum StmtView<'s, 'a> {
+    Expression(&'s (dyn Expr<'a> + 'a)),
+    Var {
+        name: Token<'a>,
+        initializer: &'s (dyn Expr<'a> + 'a),
+    },
+    Block(&'s [DynStmt<'a>]),
+    ///Statements run in the current scope,without opening a new one.
+    Statements(&'s [DynStmt<'a>]),
+    If {
+        keyword: Token<'a>,
+        condition: &'s (dyn Expr<'a> + 'a),
+        then_branch: &'s (dyn Stmt<'a> + 'a),
+        else_branch: Option<&'s (dyn Stmt<'a> + 'a)>,
+    },
+    While {
+        keyword: Token<'a>,
+        condition: &'s (dyn Expr<'a> + 'a),
+        body: &'s (dyn Stmt<'a> + 'a),
+    },
+    Function {
+        name: Token<'a>,
+        parameters: &'s [Token<'a>],
+        body: &'s (dyn Stmt<'a> + 'a),
+        ///The `///` comments in front of it.
+        doc: Option<&'s str>,
+    },
+    Return(&'s (dyn Expr<'a> + 'a)),
 }
 #[derive(Debug, Default)]
 pub struct Block<'a> {
     source: Box<[DynStmt<'a>]>,
 }
 
+impl<'a> Block<'a> {
+    pub fn statements(&self) -> &[DynStmt<'a>] {
+        &self.source
+    }
+    ///The block with every statement optimized,see `Stmt::optimize`.
+    pub fn optimized(self) -> Self {
+        Self::from(optimize_all(self.source))
+    }
+}
 impl<'a> From<Statements<'a>> for Block<'a> {
     fn from(value: Statements<'a>) -> Self {
         Self::from(value.source)
@@ -144,12 +313,18 @@ impl<'a> From<Box<[DynStmt<'a>]>> for Block<'a> {
 impl<'a> Stmt<'a> for Statements<'a> {
     fn execute(&self, env: &mut Environment<'a>) -> Result<Option<Values<'a>>, Box<dyn Error>> {
         for x in self.source.iter() {
-            if let Some(x) = x.execute(env)? {
+            if let Some(x) = execute(x.as_ref(), env)? {
                 return Ok(Some(x));
             }
         }
         Ok(None)
     }
+    fn view(&self) -> StmtView<'_, 'a> {
+        StmtView::Statements(&self.source)
+    }
+    fn optimize(self: Box<Self>) -> DynStmt<'a> {
+        Box::new(Self::from(optimize_all(self.source)))
+    }
 }
 impl<'a> From<Vec<DynStmt<'a>>> for Statements<'a> {
     fn from(value: Vec<DynStmt<'a>>) -> Self {
@@ -177,13 +352,19 @@ impl<'a> Stmt<'a> for Block<'a> {
     fn execute(&self, env: &mut Environment<'a>) -> Result<Option<Values<'a>>, Box<dyn Error>> {
         env.create_sub_values();
         for x in self.source.iter() {
-            if let Some(x) = x.execute(env)? {
+            if let Some(x) = execute(x.as_ref(), env)? {
                 return Ok(Some(x));
             }
         }
         env.delete_sub_values();
         Ok(None)
     }
+    fn view(&self) -> StmtView<'_, 'a> {
+        StmtView::Block(&self.source)
+    }
+    fn optimize(self: Box<Self>) -> DynStmt<'a> {
+        Box::new(self.optimized())
+    }
 }
 #[derive(Debug)]
 pub struct Expression<'a> {
@@ -192,20 +373,37 @@ pub struct Expression<'a> {
 #[derive(Debug)]
 pub struct Var<'a> {
     name: Token<'a>,
+    symbol: Symbol,
     initializer: Box<dyn Expr<'a> + 'a>,
 }
 impl<'a> Var<'a> {
     pub fn new(name: Token<'a>, initializer: Box<dyn Expr<'a> + 'a>) -> Self {
-        Self { name, initializer }
+        let symbol = Symbol::intern(name.as_str());
+        Self { name, symbol, initializer }
+    }
+    pub fn name(&self) -> Token<'a> {
+        self.name
     }
 }
 
 impl<'a> Stmt<'a> for Var<'a> {
     fn execute(&self, env: &mut Environment<'a>) -> Result<Option<Values<'a>>, Box<dyn Error>> {
         let val = self.initializer.evaluate_to_val(env)?;
-        env.define(self.name.to_string(), val);
+        env.define(self.symbol, val);
         Ok(None)
     }
+    fn view(&self) -> StmtView<'_, 'a> {
+        StmtView::Var {
+            name: self.name,
+            initializer: self.initializer.as_ref(),
+        }
+    }
+    fn optimize(self: Box<Self>) -> DynStmt<'a> {
+        Box::new(Self {
+            initializer: self.initializer.optimize(),
+            ..*self
+        })
+    }
 }
 impl<'a> Expression<'a> {
     pub fn new(expression: Box<dyn Expr<'a> + 'a>) -> Self {
@@ -227,6 +425,12 @@ impl<'a> Stmt<'a> for Expression<'a> {
         self.expression.evaluate_to_val(env)?;
         Ok(None)
     }
+    fn view(&self) -> StmtView<'_, 'a> {
+        StmtView::Expression(self.expression.as_ref())
+    }
+    fn optimize(self: Box<Self>) -> DynStmt<'a> {
+        Box::new(Self::new(self.expression.optimize()))
+    }
 }
 //impl<'a> Stmt<'a> for Print<'a> {
 //    fn execute(&self, env: &mut Environment<'a>) -> Result<(), Box<dyn Error>> {
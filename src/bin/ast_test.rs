@@ -2,8 +2,6 @@ use std::{io::stdout, str::FromStr};
 
 use ascii::AsciiString;
 use rlox::ast::statement::{Block, Stmt};
-use rlox::interpreter;
-use rlox::interpreter::environment::Environment;
 use rlox::lox_error::emit_error;
 use rlox::{interpreter::Interpreter, parser::Parser, token::Scanner};
 fn main() {
@@ -0,0 +1,14 @@
+use std::{
+    io::{stdin, stdout},
+    process,
+};
+
+use rlox::dap;
+
+fn main() {
+    let mut stdout = stdout();
+    if let Err(x) = dap::run(stdin().lock(), &mut stdout) {
+        eprintln!("rlox-dap:{}", x);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,17 @@
+use std::{
+    io::{stdin, stdout},
+    process,
+};
+
+use rlox::lsp::Server;
+
+fn main() {
+    let mut stdout = stdout();
+    match Server::default().run(stdin().lock(), &mut stdout) {
+        Ok(code) => process::exit(code),
+        Err(x) => {
+            eprintln!("rlox-lsp:{}", x);
+            process::exit(1);
+        }
+    }
+}
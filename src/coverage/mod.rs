@@ -0,0 +1,333 @@
+//!Which statements,branches and functions of a lox file ran,recorded through the statement hook
+//!and reported as a summary or in the LCOV format of coverage tools.
+//!
+//!What could run comes from the syntax tree of the file,what did from a `Recorder`.Branches are
+//!told apart by the line and column of their token,so the hits of runs that parsed the file again
+//!add up.
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use crate::{
+    ast::{
+        expression::{Expr, ExprView},
+        statement::{Stmt, StmtView},
+    },
+    interpreter::{debug::StatementHook, environment::Environment},
+    lox_object::Values,
+    token::Token,
+};
+
+///What ran in one run,keyed the same way as `FileCoverage`.
+#[derive(Debug, Default)]
+struct Hits {
+    lines: HashMap<usize, u64>,
+    branches: HashMap<(usize, usize), [u64; 2]>,
+    functions: HashMap<(usize, usize), u64>,
+}
+///Statement hook recording what runs.Clones share the record,so the host keeps one to read it.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder(Rc<RefCell<Hits>>);
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl<'a> StatementHook<'a> for Recorder {
+    fn before_statement(
+        &mut self,
+        line: usize,
+        _env: &Environment<'a>,
+    ) -> Result<(), Box<dyn Error>> {
+        *self.0.borrow_mut().lines.entry(line).or_default() += 1;
+        Ok(())
+    }
+    fn call(&mut self, _arguments: &[Values<'a>], env: &Environment<'a>) {
+        if let Some(frame) = env.frames().last() {
+            let key = position(frame.function);
+            *self.0.borrow_mut().functions.entry(key).or_default() += 1;
+        }
+    }
+    fn branch(&mut self, at: Token<'a>, arm: usize, _env: &Environment<'a>) {
+        self.0
+            .borrow_mut()
+            .branches
+            .entry(position(at))
+            .or_default()[arm] += 1;
+    }
+}
+fn position(token: Token<'_>) -> (usize, usize) {
+    (token.line(), token.column())
+}
+
+///The kind of a branch,for the report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BranchKind {
+    If,
+    Loop,
+    Logical,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    pub line: usize,
+    pub kind: BranchKind,
+    ///Hits of the two arms,see `StatementHook::branch`.
+    pub hits: [u64; 2],
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub line: usize,
+    pub calls: u64,
+}
+///The coverage of one file,everything that could run with how often it did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileCoverage {
+    pub path: String,
+    ///Hits of every line a statement starts on.
+    pub lines: BTreeMap<usize, u64>,
+    ///Keyed by line and column of their token.
+    pub branches: BTreeMap<(usize, usize), Branch>,
+    ///Keyed by line and column of their name.
+    pub functions: BTreeMap<(usize, usize), Function>,
+}
+impl FileCoverage {
+    ///Everything in `ast`,the code of `path`,that could run,with no hits yet.
+    pub fn new<'a>(path: &str, ast: &(dyn Stmt<'a> + 'a)) -> Self {
+        let mut output = Self {
+            path: path.to_string(),
+            ..Self::default()
+        };
+        output.statement(ast);
+        output
+    }
+    fn statement<'a>(&mut self, statement: &(dyn Stmt<'a> + 'a)) {
+        if let Some(line) = statement.line() {
+            self.lines.insert(line, 0);
+        }
+        match statement.view() {
+            StmtView::Expression(x) | StmtView::Return(x) => self.expression(x),
+            StmtView::Var { initializer, .. } => self.expression(initializer),
+            StmtView::Block(x) | StmtView::Statements(x) => {
+                x.iter().for_each(|x| self.statement(x.as_ref()))
+            }
+            StmtView::If {
+                keyword,
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.branch(keyword, BranchKind::If);
+                self.expression(condition);
+                self.statement(then_branch);
+                else_branch.into_iter().for_each(|x| self.statement(x));
+            }
+            StmtView::While {
+                keyword,
+                condition,
+                body,
+            } => {
+                self.branch(keyword, BranchKind::Loop);
+                self.expression(condition);
+                self.statement(body);
+            }
+            StmtView::Function { name, body, .. } => {
+                let function = Function {
+                    name: name.as_str().to_string(),
+                    line: name.line(),
+                    calls: 0,
+                };
+                self.functions.insert(position(name), function);
+                self.statement(body);
+            }
+        }
+    }
+    fn expression<'a>(&mut self, expression: &(dyn Expr<'a> + 'a)) {
+        match expression.view() {
+            ExprView::Binary { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            ExprView::Logical {
+                left,
+                operator,
+                right,
+            } => {
+                self.branch(operator, BranchKind::Logical);
+                self.expression(left);
+                self.expression(right);
+            }
+            ExprView::Unary { right: x, .. }
+            | ExprView::Grouping(x)
+            | ExprView::Assign { value: x, .. } => self.expression(x),
+            ExprView::Call {
+                callee, arguments, ..
+            } => {
+                self.expression(callee);
+                arguments.iter().for_each(|x| self.expression(x.as_ref()));
+            }
+            ExprView::Literal(_) | ExprView::Value(_) | ExprView::Variable(_) => (),
+        }
+    }
+    fn branch(&mut self, at: Token<'_>, kind: BranchKind) {
+        let branch = Branch {
+            line: at.line(),
+            kind,
+            hits: [0; 2],
+        };
+        self.branches.insert(position(at), branch);
+    }
+    ///Add what `recorder` saw.Hits of code that isn't part of the file are left out.
+    pub fn add(&mut self, recorder: &Recorder) {
+        let hits = recorder.0.borrow();
+        for (line, count) in &hits.lines {
+            if let Some(x) = self.lines.get_mut(line) {
+                *x += count;
+            }
+        }
+        for (key, [then, other]) in &hits.branches {
+            if let Some(x) = self.branches.get_mut(key) {
+                x.hits[0] += then;
+                x.hits[1] += other;
+            }
+        }
+        for (key, calls) in &hits.functions {
+            if let Some(x) = self.functions.get_mut(key) {
+                x.calls += calls;
+            }
+        }
+    }
+    ///Lines that ran and lines that could.
+    pub fn line_counts(&self) -> (usize, usize) {
+        let hit = self.lines.values().filter(|x| **x > 0).count();
+        (hit, self.lines.len())
+    }
+    ///Arms of the branches that were taken and arms there are.
+    pub fn branch_counts(&self) -> (usize, usize) {
+        let arms = self.branches.values().flat_map(|x| x.hits);
+        let hit = arms.filter(|x| *x > 0).count();
+        (hit, self.branches.len() * 2)
+    }
+    pub fn function_counts(&self) -> (usize, usize) {
+        let hit = self.functions.values().filter(|x| x.calls > 0).count();
+        (hit, self.functions.len())
+    }
+    ///Lines that never ran,as ranges of lines that could run.
+    pub fn missed_lines(&self) -> Vec<(usize, usize)> {
+        let mut output: Vec<(usize, usize)> = Vec::new();
+        let mut previous_missed = false;
+        for (line, hits) in &self.lines {
+            match output.last_mut() {
+                Some(x) if *hits == 0 && previous_missed => x.1 = *line,
+                _ if *hits == 0 => output.push((*line, *line)),
+                _ => (),
+            }
+            previous_missed = *hits == 0;
+        }
+        output
+    }
+    ///Write the record of the file in the LCOV tracefile format.
+    pub fn write_lcov(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "TN:")?;
+        writeln!(out, "SF:{}", self.path)?;
+        for x in self.functions.values() {
+            writeln!(out, "FN:{},{}", x.line, x.name)?;
+        }
+        for x in self.functions.values() {
+            writeln!(out, "FNDA:{},{}", x.calls, x.name)?;
+        }
+        let (hit, found) = self.function_counts();
+        writeln!(out, "FNF:{}\nFNH:{}", found, hit)?;
+        //The branches of a line are numbered in the order of their columns.
+        let mut block = 0;
+        let mut previous_line = 0;
+        for x in self.branches.values() {
+            block = if x.line == previous_line {
+                block + 1
+            } else {
+                0
+            };
+            previous_line = x.line;
+            for (arm, hits) in x.hits.iter().enumerate() {
+                //`-` is a branch whose code never ran.
+                let taken = match x.hits {
+                    [0, 0] => "-".to_string(),
+                    _ => hits.to_string(),
+                };
+                writeln!(out, "BRDA:{},{},{},{}", x.line, block, arm, taken)?;
+            }
+        }
+        let (hit, found) = self.branch_counts();
+        writeln!(out, "BRF:{}\nBRH:{}", found, hit)?;
+        for (line, hits) in &self.lines {
+            writeln!(out, "DA:{},{}", line, hits)?;
+        }
+        let (hit, found) = self.line_counts();
+        writeln!(out, "LF:{}\nLH:{}", found, hit)?;
+        writeln!(out, "end_of_record")
+    }
+}
+
+///Write the LCOV tracefile of `files`.
+pub fn write_lcov(files: &[FileCoverage], out: &mut dyn Write) -> io::Result<()> {
+    for x in files {
+        x.write_lcov(out)?;
+    }
+    Ok(())
+}
+///Write a line for each file and the total:the lines,branches and functions covered and the
+///lines that were missed.
+pub fn write_summary(files: &[FileCoverage], out: &mut dyn Write) -> io::Result<()> {
+    fn percent((hit, found): (usize, usize)) -> String {
+        match found {
+            0 => format!("{}/{}", hit, found),
+            _ => format!(
+                "{}/{} ({:.1}%)",
+                hit,
+                found,
+                hit as f64 * 100.0 / found as f64
+            ),
+        }
+    }
+    fn add(x: (usize, usize), y: (usize, usize)) -> (usize, usize) {
+        (x.0 + y.0, x.1 + y.1)
+    }
+    writeln!(out, "Coverage:")?;
+    let mut total = [(0, 0); 3];
+    for x in files {
+        let counts = [x.line_counts(), x.branch_counts(), x.function_counts()];
+        for (total, counts) in total.iter_mut().zip(counts) {
+            *total = add(*total, counts);
+        }
+        writeln!(
+            out,
+            "  {}\n    lines {},branches {},functions {}",
+            x.path,
+            percent(counts[0]),
+            percent(counts[1]),
+            percent(counts[2])
+        )?;
+        let missed: Vec<String> = x
+            .missed_lines()
+            .into_iter()
+            .map(|(start, end)| match start == end {
+                true => start.to_string(),
+                false => format!("{}-{}", start, end),
+            })
+            .collect();
+        if !missed.is_empty() {
+            writeln!(out, "    missed lines {}", missed.join(","))?;
+        }
+    }
+    writeln!(
+        out,
+        "  total lines {},branches {},functions {}",
+        percent(total[0]),
+        percent(total[1]),
+        percent(total[2])
+    )
+}
@@ -0,0 +1,524 @@
+//!A debug adapter for lox,speaking the Debug Adapter Protocol over a reader and a writer,usually
+//!stdin and stdout of the `rlox-dap` binary.
+//!
+//!There is one thread running one file.The script runs inside the adapter with a statement hook,
+//!when the hook decides to stop it sends a `stopped` event and answers requests until the client
+//!resumes.Whatever the script prints is sent as `output` events.
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashSet},
+    error::Error,
+    fs,
+    io::{self, BufRead, Write},
+    rc::Rc,
+};
+
+use crate::{
+    ast::statement::{Stmt, StmtView},
+    interpreter::{
+        debug::StatementHook,
+        environment::{Environment, Scope},
+        process::Exit,
+        Interpreter,
+    },
+    json::{self, Json},
+    lox_object::Values,
+    lox_runner::code_to_stblock_with,
+    lsp::{read_message, write_message},
+};
+
+const THREAD_ID: f64 = 1.0;
+
+///Serve one debugging session,until the client disconnects or closes `input`.
+pub fn run<'io>(input: impl BufRead + 'io, output: &'io mut dyn Write) -> io::Result<()> {
+    let session = Rc::new(RefCell::new(Session::new(Box::new(input), output)));
+    //The client sets its breakpoints between `launch` and `configurationDone`.
+    loop {
+        let mut brw = session.borrow_mut();
+        let Some(request) = brw.next_request()? else {
+            return Ok(());
+        };
+        match command(&request) {
+            "configurationDone" => {
+                brw.respond(&request, Ok(Json::Null))?;
+                break;
+            }
+            "disconnect" => return brw.respond(&request, Ok(Json::Null)),
+            _ => brw.handle(&request)?,
+        }
+    }
+    let exit_code = launch(&session)?;
+    let mut brw = session.borrow_mut();
+    if brw.disconnected {
+        return Ok(());
+    }
+    brw.event("exited", Json::object([("exitCode", exit_code.into())]))?;
+    brw.event("terminated", Json::object([]))?;
+    while let Some(request) = brw.next_request()? {
+        if command(&request) == "disconnect" {
+            return brw.respond(&request, Ok(Json::Null));
+        }
+        brw.handle(&request)?;
+    }
+    Ok(())
+}
+///Run the launched program under the debugger,giving back its exit code.
+fn launch(session: &Rc<RefCell<Session<'_>>>) -> io::Result<f64> {
+    let mut stdout = ScriptOutput(session.clone());
+    let Some(program) = session.borrow().program.clone() else {
+        session
+            .borrow_mut()
+            .output("stderr", "No program was launched.\n")?;
+        return Ok(1.0);
+    };
+    //Optimizing would drop the statements of constant branches,and their lines with them.
+    let ast = match code_to_stblock_with(&program.code, false) {
+        Ok(x) => x,
+        Err(x) => {
+            let message = format!("{}\n", x);
+            session.borrow_mut().output("stderr", &message)?;
+            return Ok(65.0);
+        }
+    };
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.set_filesystem_access(true);
+    interpreter.set_args(program.args);
+    interpreter.set_statement_hook(Some(Box::new(Debugger(session.clone()))));
+    let result = interpreter.try_interpret(&ast);
+    drop(interpreter);
+    let Err(error) = result else {
+        return Ok(0.0);
+    };
+    let mut brw = session.borrow_mut();
+    if let Some(Exit(code)) = error.downcast_ref::<Exit>() {
+        return Ok(*code as f64);
+    }
+    if !brw.stopped {
+        brw.output("stderr", &format!("{}\n", error))?;
+    }
+    Ok(70.0)
+}
+
+fn command(request: &Json) -> &str {
+    request.get("command").and_then(Json::as_str).unwrap_or("")
+}
+fn argument<'j>(request: &'j Json, name: &str) -> Option<&'j Json> {
+    request.get("arguments").and_then(|x| x.get(name))
+}
+
+#[derive(Debug, Clone)]
+struct Program {
+    path: String,
+    code: String,
+    args: Vec<String>,
+}
+///How the script goes on after a stop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Resume {
+    ///Stop at the first statement.
+    Entry,
+    Continue,
+    ///Stop at the next statement,in any function.
+    StepIn,
+    ///Stop at the next line without entering the calls,from a call this deep.
+    Next(usize),
+    ///Stop once the call this deep returned.
+    StepOut(usize),
+}
+#[derive(Debug)]
+struct StackFrame {
+    name: String,
+    line: usize,
+    ///Name and variables reference of each scope.
+    scopes: Vec<(&'static str, usize)>,
+}
+#[derive(Debug)]
+struct Variable {
+    name: String,
+    value: String,
+    kind: &'static str,
+    ///The elements of a list,0 for everything else.
+    reference: usize,
+}
+///What the client can look at while the script is stopped.
+#[derive(Debug, Default)]
+struct Stop {
+    frames: Vec<StackFrame>,
+    ///The variables behind each variables reference,the first one is 1.
+    variables: Vec<Vec<Variable>>,
+}
+impl Stop {
+    fn new<'a>(line: usize, env: &Environment<'a>) -> Self {
+        let mut output = Self::default();
+        let frames = env.frames();
+        //The innermost call is stopped at `line`,each caller at the line of the call it made.
+        let mut line = line;
+        let mut scope = env.get_current();
+        for depth in (0..=frames.len()).rev() {
+            let call = depth.checked_sub(1).map(|x| &frames[x]);
+            let name = call.map_or("main".to_string(), |x| x.function.as_str().to_string());
+            let closure = call.map(|x| &x.closure);
+            let mut scopes = vec![("Locals", output.scope(&scope, closure, env))];
+            if let Some(closure) = closure {
+                scopes.push(("Closure", output.scope(closure, None, env)));
+            }
+            output.frames.push(StackFrame { name, line, scopes });
+            if let Some(call) = call {
+                line = call.call_line;
+                scope = call.caller.clone();
+            }
+        }
+        output
+    }
+    ///Register the variables of `scope` and its parents up to `end` or the built-ins,giving back
+    ///their reference.An inner variable hides the outer ones with its name.
+    fn scope<'a>(
+        &mut self,
+        scope: &Scope<'a>,
+        end: Option<&Scope<'a>>,
+        env: &Environment<'a>,
+    ) -> usize {
+        let mut seen = HashSet::new();
+        let mut values = Vec::new();
+        let mut next = Some(scope.clone());
+        while let Some(scope) = next {
+            if end.is_some_and(|x| x.same(&scope)) || env.builtins().same(&scope) {
+                break;
+            }
+            for (name, value) in scope.variables() {
+                if seen.insert(name) {
+                    values.push((name.to_string(), value));
+                }
+            }
+            next = scope.parent();
+        }
+        values.sort_by(|x, y| x.0.cmp(&y.0));
+        self.register(values)
+    }
+    fn register(&mut self, values: Vec<(String, Values<'_>)>) -> usize {
+        let reference = self.variables.len() + 1;
+        self.variables.push(Vec::new());
+        let variables = values
+            .into_iter()
+            .map(|(name, value)| {
+                let reference = match &value {
+                    Values::List(x) => {
+                        let elements = x.iter().enumerate();
+                        self.register(elements.map(|(i, x)| (i.to_string(), x.clone())).collect())
+                    }
+                    _ => 0,
+                };
+                Variable {
+                    name,
+                    value: value.repr(),
+                    kind: value.type_name(),
+                    reference,
+                }
+            })
+            .collect();
+        self.variables[reference - 1] = variables;
+        reference
+    }
+}
+
+struct Session<'io> {
+    input: Box<dyn BufRead + 'io>,
+    output: &'io mut dyn Write,
+    seq: usize,
+    program: Option<Program>,
+    breakpoints: HashSet<usize>,
+    resume: Resume,
+    ///Line and call depth of the last statement.
+    last: (usize, usize),
+    stop: Option<Stop>,
+    ///The client asked to end the script.
+    stopped: bool,
+    disconnected: bool,
+}
+impl<'io> Session<'io> {
+    fn new(input: Box<dyn BufRead + 'io>, output: &'io mut dyn Write) -> Self {
+        Self {
+            input,
+            output,
+            seq: 0,
+            program: None,
+            breakpoints: HashSet::new(),
+            resume: Resume::Continue,
+            last: (0, 0),
+            stop: None,
+            stopped: false,
+            disconnected: false,
+        }
+    }
+    ///The next request,`None` when the input ended.Messages that aren't JSON are skipped.
+    fn next_request(&mut self) -> io::Result<Option<Json>> {
+        while let Some(body) = read_message(&mut self.input)? {
+            if let Ok(x) = json::parse(&body) {
+                return Ok(Some(x));
+            }
+        }
+        Ok(None)
+    }
+    fn send(&mut self, kind: &str, fields: Vec<(&str, Json)>) -> io::Result<()> {
+        self.seq += 1;
+        let mut message = vec![("seq", self.seq.into()), ("type", kind.into())];
+        message.extend(fields);
+        write_message(self.output, &Json::object(message))
+    }
+    fn respond(&mut self, request: &Json, body: Result<Json, String>) -> io::Result<()> {
+        let mut fields = vec![
+            (
+                "request_seq",
+                request.get("seq").cloned().unwrap_or(Json::Null),
+            ),
+            ("command", command(request).into()),
+            ("success", body.is_ok().into()),
+        ];
+        match body {
+            Ok(Json::Null) => (),
+            Ok(x) => fields.push(("body", x)),
+            Err(x) => fields.push(("message", x.into())),
+        }
+        self.send("response", fields)
+    }
+    fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        self.send("event", vec![("event", event.into()), ("body", body)])
+    }
+    fn output(&mut self, category: &str, text: &str) -> io::Result<()> {
+        let body = Json::object([("category", category.into()), ("output", text.into())]);
+        self.event("output", body)
+    }
+    ///Answer a request that doesn't change whether the script runs.
+    fn handle(&mut self, request: &Json) -> io::Result<()> {
+        let body = self.answer(request);
+        self.respond(request, body)?;
+        if command(request) == "initialize" {
+            self.event("initialized", Json::object([]))?;
+        }
+        Ok(())
+    }
+    fn answer(&mut self, request: &Json) -> Result<Json, String> {
+        match command(request) {
+            "initialize" => Ok(Json::object([
+                ("supportsConfigurationDoneRequest", true.into()),
+                ("supportsTerminateRequest", true.into()),
+            ])),
+            "launch" => {
+                let path = argument(request, "program").and_then(Json::as_str);
+                let path = path.ok_or("Missing the program to launch.")?;
+                let code =
+                    fs::read_to_string(path).map_err(|x| format!("Can't read {}:{}", path, x))?;
+                let args = argument(request, "args")
+                    .and_then(Json::as_array)
+                    .unwrap_or(&[]);
+                let args = args.iter().filter_map(Json::as_str).map(str::to_string);
+                if argument(request, "stopOnEntry").and_then(Json::as_bool) == Some(true) {
+                    self.resume = Resume::Entry;
+                }
+                self.program = Some(Program {
+                    path: path.to_string(),
+                    code,
+                    args: args.collect(),
+                });
+                Ok(Json::Null)
+            }
+            "setBreakpoints" => {
+                let requested = argument(request, "breakpoints").and_then(Json::as_array);
+                let requested = requested.unwrap_or(&[]).iter();
+                let lines: Vec<usize> = requested
+                    .filter_map(|x| x.get("line").and_then(Json::as_f64))
+                    .map(|x| x as usize)
+                    .collect();
+                let statements = self.program.as_ref().map(|x| statement_lines(&x.code));
+                self.breakpoints = lines.iter().copied().collect();
+                let breakpoints = lines.into_iter().map(|line| {
+                    let verified = statements.as_ref().is_none_or(|x| x.contains(&line));
+                    Json::object([("verified", verified.into()), ("line", line.into())])
+                });
+                Ok(Json::object([(
+                    "breakpoints",
+                    Json::Array(breakpoints.collect()),
+                )]))
+            }
+            "setExceptionBreakpoints" => Ok(Json::Null),
+            "threads" => {
+                let thread = Json::object([("id", THREAD_ID.into()), ("name", "main".into())]);
+                Ok(Json::object([("threads", Json::Array(vec![thread]))]))
+            }
+            "stackTrace" => {
+                let stop = self.stop.as_ref().ok_or("The script isn't stopped.")?;
+                let source = self.program.as_ref().map(|x| {
+                    Json::object([
+                        ("name", x.path.as_str().into()),
+                        ("path", x.path.as_str().into()),
+                    ])
+                });
+                let frames = stop.frames.iter().enumerate().map(|(i, x)| {
+                    let mut frame = vec![
+                        ("id", i.into()),
+                        ("name", x.name.as_str().into()),
+                        ("line", x.line.into()),
+                        ("column", 1.0.into()),
+                    ];
+                    frame.extend(source.clone().map(|x| ("source", x)));
+                    Json::object(frame)
+                });
+                Ok(Json::object([
+                    ("stackFrames", Json::Array(frames.collect())),
+                    ("totalFrames", stop.frames.len().into()),
+                ]))
+            }
+            "scopes" => {
+                let stop = self.stop.as_ref().ok_or("The script isn't stopped.")?;
+                let id = argument(request, "frameId").and_then(Json::as_f64);
+                let frame = id.and_then(|x| stop.frames.get(x as usize));
+                let frame = frame.ok_or("Unknown frame.")?;
+                let scopes = frame.scopes.iter().map(|(name, reference)| {
+                    Json::object([
+                        ("name", (*name).into()),
+                        ("variablesReference", (*reference).into()),
+                        ("expensive", false.into()),
+                    ])
+                });
+                Ok(Json::object([("scopes", Json::Array(scopes.collect()))]))
+            }
+            "variables" => {
+                let stop = self.stop.as_ref().ok_or("The script isn't stopped.")?;
+                let reference = argument(request, "variablesReference").and_then(Json::as_f64);
+                let reference = reference.map_or(0, |x| x as usize);
+                let variables = reference.checked_sub(1).and_then(|x| stop.variables.get(x));
+                let variables = variables.ok_or("Unknown variables reference.")?;
+                let variables = variables.iter().map(|x| {
+                    Json::object([
+                        ("name", x.name.as_str().into()),
+                        ("value", x.value.as_str().into()),
+                        ("type", x.kind.into()),
+                        ("variablesReference", x.reference.into()),
+                    ])
+                });
+                Ok(Json::object([(
+                    "variables",
+                    Json::Array(variables.collect()),
+                )]))
+            }
+            "continue" | "next" | "stepIn" | "stepOut" | "pause" => {
+                Err("The script isn't stopped.".to_string())
+            }
+            x => Err(format!("Unsupported request {}.", x)),
+        }
+    }
+    ///Why the statement on `line`,`depth` calls deep,should stop the script.
+    fn stop_reason(&self, line: usize, depth: usize) -> Option<&'static str> {
+        //Statements nested on one line count as one stop.
+        let moved = (line, depth) != self.last;
+        let step = match self.resume {
+            Resume::Entry => return Some("entry"),
+            Resume::Continue => false,
+            Resume::StepIn => moved,
+            Resume::Next(from) => depth < from || (depth == from && moved),
+            Resume::StepOut(from) => depth < from,
+        };
+        if step {
+            Some("step")
+        } else if moved && self.breakpoints.contains(&line) {
+            Some("breakpoint")
+        } else {
+            None
+        }
+    }
+    ///Answer requests until the client resumes the script.An error ends the script.
+    fn wait(&mut self, depth: usize) -> Result<(), Box<dyn Error>> {
+        loop {
+            let Some(request) = self.next_request()? else {
+                self.disconnected = true;
+                return Err("The debugger went away.".into());
+            };
+            let resume = match command(&request) {
+                "continue" => Resume::Continue,
+                "next" => Resume::Next(depth),
+                "stepIn" => Resume::StepIn,
+                "stepOut" => Resume::StepOut(depth),
+                x @ ("disconnect" | "terminate") => {
+                    self.respond(&request, Ok(Json::Null))?;
+                    self.stopped = true;
+                    self.disconnected = x == "disconnect";
+                    return Err("The debugger stopped the script.".into());
+                }
+                _ => {
+                    self.handle(&request)?;
+                    continue;
+                }
+            };
+            self.resume = resume;
+            self.stop = None;
+            let body = Json::object([("allThreadsContinued", true.into())]);
+            return Ok(self.respond(&request, Ok(body))?);
+        }
+    }
+}
+
+///The statement hook of the script,stopping it where the client asked.
+struct Debugger<'io>(Rc<RefCell<Session<'io>>>);
+impl<'a> StatementHook<'a> for Debugger<'_> {
+    fn before_statement(
+        &mut self,
+        line: usize,
+        env: &Environment<'a>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut session = self.0.borrow_mut();
+        let depth = env.frames().len();
+        let reason = session.stop_reason(line, depth);
+        session.last = (line, depth);
+        let Some(reason) = reason else {
+            return Ok(());
+        };
+        session.stop = Some(Stop::new(line, env));
+        let body = Json::object([
+            ("reason", reason.into()),
+            ("threadId", THREAD_ID.into()),
+            ("allThreadsStopped", true.into()),
+        ]);
+        session.event("stopped", body)?;
+        session.wait(depth)
+    }
+}
+
+///Stdout of the script,sent to the client as `output` events.
+struct ScriptOutput<'io>(Rc<RefCell<Session<'io>>>);
+impl Write for ScriptOutput<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0.borrow_mut().output("stdout", &text)?;
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+///Lines a statement starts on,where a breakpoint can stop.
+fn statement_lines(code: &str) -> BTreeSet<usize> {
+    fn walk<'a>(statement: &(dyn Stmt<'a> + 'a), output: &mut BTreeSet<usize>) {
+        output.extend(statement.line());
+        match statement.view() {
+            StmtView::Block(x) | StmtView::Statements(x) => {
+                x.iter().for_each(|x| walk(x.as_ref(), output))
+            }
+            StmtView::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                walk(then_branch, output);
+                else_branch.into_iter().for_each(|x| walk(x, output));
+            }
+            StmtView::While { body, .. } | StmtView::Function { body, .. } => walk(body, output),
+            _ => (),
+        }
+    }
+    let mut output = BTreeSet::new();
+    if let Ok(ast) = code_to_stblock_with(code, false) {
+        walk(&ast, &mut output);
+    }
+    output
+}
@@ -0,0 +1,100 @@
+//!`rlox doc`,API documentation of lox files from the `///` comments of their functions.
+//!
+//!Every function declared at the top of a file is listed with its parameters,the text of its
+//!comments and the line it is declared on,in the order of the file.Functions declared inside
+//!others are left out,they can't be called from outside.
+use std::error::Error;
+
+use crate::{
+    ast::statement::{Stmt, StmtView},
+    highlight::escape,
+    lox_runner::code_to_stblock_with,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDoc {
+    pub name: String,
+    pub parameters: Vec<String>,
+    ///Text of the `///` comments,`None` when there are none.
+    pub doc: Option<String>,
+    pub line: usize,
+}
+impl FunctionDoc {
+    ///`name(a, b)`
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, self.parameters.join(", "))
+    }
+}
+
+///How `document` writes the documentation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Markdown,
+    Html,
+}
+
+///The functions of `code`,giving back the scanner or parser errors if it isn't a valid program.
+pub fn functions<'a>(code: &'a str) -> Result<Vec<FunctionDoc>, Box<dyn Error + 'a>> {
+    let ast = code_to_stblock_with(code, false)?;
+    let mut output = Vec::new();
+    if let StmtView::Block(x) | StmtView::Statements(x) = ast.view() {
+        for x in x {
+            if let StmtView::Function {
+                name,
+                parameters,
+                doc,
+                ..
+            } = x.view()
+            {
+                output.push(FunctionDoc {
+                    name: name.as_str().to_string(),
+                    parameters: parameters.iter().map(|x| x.as_str().to_string()).collect(),
+                    doc: doc.map(str::to_string),
+                    line: name.line(),
+                });
+            }
+        }
+    }
+    Ok(output)
+}
+
+///The documentation of `functions`,the functions of the file at `path`,in `format`.
+pub fn document(path: &str, functions: &[FunctionDoc], format: Format) -> String {
+    match format {
+        Format::Markdown => markdown(path, functions),
+        Format::Html => html(path, functions),
+    }
+}
+fn markdown(path: &str, functions: &[FunctionDoc]) -> String {
+    let mut output = format!("# {}\n", path);
+    for x in functions {
+        output.push_str(&format!("\n## `{}`\n\n", x.signature()));
+        if let Some(doc) = &x.doc {
+            output.push_str(&format!("{}\n\n", doc));
+        }
+        output.push_str(&format!("Defined at `{}:{}`.\n", path, x.line));
+    }
+    output
+}
+fn html(path: &str, functions: &[FunctionDoc]) -> String {
+    let mut output = String::from("<section class=\"lox-doc\">\n<h1>");
+    escape(path, &mut output);
+    output.push_str("</h1>\n");
+    for x in functions {
+        output.push_str("<h2><code>");
+        escape(&x.signature(), &mut output);
+        output.push_str("</code></h2>\n");
+        //Paragraphs are split by blank lines,the way Markdown does.
+        let doc = x.doc.as_deref().unwrap_or_default();
+        for paragraph in doc.split("\n\n").filter(|x| !x.trim().is_empty()) {
+            output.push_str("<p>");
+            escape(paragraph.trim(), &mut output);
+            output.push_str("</p>\n");
+        }
+        output.push_str("<p class=\"location\">Defined at <code>");
+        escape(&format!("{}:{}", path, x.line), &mut output);
+        output.push_str("</code>.</p>\n");
+    }
+    output.push_str("</section>\n");
+    output
+}
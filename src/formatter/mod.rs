@@ -0,0 +1,190 @@
+//!Re-prints lox code in one style:two spaces for each block,braces on the line of their
+//!statement,one space around binary operators and after commas.
+//!
+//!The printer walks the tokens instead of the syntax tree,the tree has lost the `for` loops and
+//!the comments.Comments come from the trivia of the tokens,a comment written after code stays
+//!at the end of that line and one written on its own line stays on its own line.Runs of blank
+//!lines between statements become one.Formatting the output again gives back the same text.
+use std::error::Error;
+
+use ascii::AsAsciiStr;
+
+use crate::{
+    parser::Parser,
+    token::{Scanner, Token, TokenType},
+};
+
+const INDENT: &str = "  ";
+
+///Format `code`,giving back the scanner or parser errors if it isn't a valid program.
+pub fn format_code<'a>(code: &'a str) -> Result<String, Box<dyn Error + 'a>> {
+    let tokens = Scanner::new(code.as_ascii_str()?).scan_tokens()?;
+    Parser::new(&tokens).parse()?;
+    let mut printer = Printer::default();
+    for x in &tokens {
+        printer.token(*x);
+    }
+    Ok(printer.output)
+}
+
+///What goes between two tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Gap {
+    Nothing,
+    Space,
+    Newline,
+}
+
+///A comment in the trivia of a token,with the number of line breaks in front of it.
+struct Comment<'a> {
+    breaks: usize,
+    text: &'a str,
+}
+///The comments of `trivia` and the line breaks after the last of them.
+fn comments(trivia: &str) -> (Vec<Comment<'_>>, usize) {
+    let mut output = Vec::new();
+    let mut breaks = 0;
+    let mut rest = trivia;
+    while let Some(x) = rest.find(['\n', '/']) {
+        if rest.as_bytes()[x] == b'\n' {
+            breaks += 1;
+            rest = &rest[x + 1..];
+            continue;
+        }
+        //Only comments and white space are trivia,so a slash starts a comment.
+        let end = rest[x..].find('\n').map_or(rest.len(), |end| x + end);
+        output.push(Comment {
+            breaks,
+            text: rest[x..end].trim_end(),
+        });
+        breaks = 0;
+        rest = &rest[end..];
+    }
+    (output, breaks)
+}
+
+#[derive(Debug, Default)]
+struct Printer<'a> {
+    output: String,
+    depth: usize,
+    ///Parentheses left open,the semicolons of a `for` header don't end lines.
+    parens: usize,
+    previous: Option<Token<'a>>,
+    ///Whether the previous token was a `-` or a `!` in front of its operand.
+    previous_unary: bool,
+}
+impl<'a> Printer<'a> {
+    ///Start a new line,one level deeper when it continues the line before.
+    fn newline(&mut self, blank: bool, continuation: bool) {
+        if blank {
+            self.output.push('\n');
+        }
+        self.output.push('\n');
+        //A line broken inside parentheses,by a comment,is a continuation.
+        let depth = self.depth + usize::from(continuation || self.parens > 0);
+        self.output.push_str(&INDENT.repeat(depth));
+    }
+    ///Whether `-` or `!` after the previous token is a unary operator.
+    fn unary_position(&self) -> bool {
+        !self.previous.is_some_and(|x| {
+            x.matches_token(&[
+                TokenType::Identifier,
+                TokenType::Number,
+                TokenType::String,
+                TokenType::True,
+                TokenType::False,
+                TokenType::Nil,
+                TokenType::This,
+                TokenType::RightParen,
+            ])
+        })
+    }
+    fn gap(&self, next: Token<'a>) -> Gap {
+        use TokenType as T;
+        let Some(previous) = self.previous else {
+            return Gap::Nothing;
+        };
+        match (previous.get_type(), next.get_type()) {
+            (_, T::Eof) => Gap::Nothing,
+            (T::Semicolon, T::Semicolon | T::RightParen) if self.parens > 0 => Gap::Nothing,
+            (T::Semicolon, _) if self.parens > 0 => Gap::Space,
+            //`if (a) b(); else c();` stays on one line.
+            (T::Semicolon, T::Else) => Gap::Space,
+            (T::Semicolon, _) => Gap::Newline,
+            (T::LeftBrace, T::RightBrace) => Gap::Nothing,
+            (T::LeftBrace, _) => Gap::Newline,
+            (T::RightBrace, T::Else) => Gap::Space,
+            (T::RightBrace, T::RightParen | T::Semicolon | T::Comma) => Gap::Nothing,
+            (T::RightBrace, _) => Gap::Newline,
+            (_, T::Semicolon | T::Comma | T::RightParen | T::Dot) => Gap::Nothing,
+            (T::LeftParen | T::Dot, _) => Gap::Nothing,
+            (T::Minus | T::Bang, _) if self.previous_unary => Gap::Nothing,
+            //A call.
+            (T::Identifier | T::RightParen, T::LeftParen) => Gap::Nothing,
+            _ => Gap::Space,
+        }
+    }
+    fn token(&mut self, token: Token<'a>) {
+        let mut gap = self.gap(token);
+        //A comment that breaks a statement in the middle pushes the rest of it to the next line.
+        let at_start = self.previous.is_none();
+        let continuation = !at_start
+            && gap != Gap::Newline
+            && !token.matches_token(&[TokenType::Else, TokenType::RightBrace, TokenType::Eof]);
+        //Blank lines are kept between statements,not at the top or the bottom of a block.
+        let mut top = self
+            .previous
+            .is_some_and(|x| x.match_token(&TokenType::LeftBrace));
+        let (comments, breaks) = comments(token.trivia());
+        for (i, comment) in comments.iter().enumerate() {
+            let first = at_start && i == 0;
+            if !first && comment.breaks == 0 {
+                self.output.push(' ');
+            } else if !first {
+                self.newline(comment.breaks > 1 && !top, continuation);
+                top = false;
+            }
+            self.output.push_str(comment.text);
+            gap = Gap::Newline;
+        }
+        if token.match_token(&TokenType::RightBrace) {
+            self.depth = self.depth.saturating_sub(1);
+        }
+        if token.match_token(&TokenType::Eof) {
+            if !self.output.is_empty() {
+                self.output.push('\n');
+            }
+            return;
+        }
+        match gap {
+            Gap::Nothing => (),
+            Gap::Space => self.output.push(' '),
+            Gap::Newline => {
+                let bottom = token.match_token(&TokenType::RightBrace);
+                self.newline(breaks > 1 && !top && !bottom, continuation);
+            }
+        }
+        self.print(token);
+    }
+    fn print(&mut self, token: Token<'a>) {
+        let unary =
+            token.matches_token(&[TokenType::Minus, TokenType::Bang]) && self.unary_position();
+        match token.get_type() {
+            //The lexeme of a string is without its quotes.
+            TokenType::String => {
+                self.output.push('"');
+                self.output.push_str(token.as_str());
+                self.output.push('"');
+            }
+            _ => self.output.push_str(token.as_str()),
+        }
+        match token.get_type() {
+            TokenType::LeftBrace => self.depth += 1,
+            TokenType::LeftParen => self.parens += 1,
+            TokenType::RightParen => self.parens = self.parens.saturating_sub(1),
+            _ => (),
+        }
+        self.previous = Some(token);
+        self.previous_unary = unary;
+    }
+}
@@ -0,0 +1,192 @@
+//!Colouring lox code,for terminals with ANSI escapes or for web pages with HTML.
+//!
+//!Every token is classified by its type.The white space and comments between tokens come from
+//!their trivia,so the output keeps the text of the source as it was written.
+use std::error::Error;
+
+use ascii::AsAsciiStr;
+
+use crate::token::{Scanner, TokenType};
+
+///What a piece of the source is,for choosing its colour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    ///Keywords,`true`,`false` and `nil` included.
+    Keyword,
+    String,
+    Number,
+    Identifier,
+    Operator,
+    ///Parentheses,braces,commas,dots and semicolons.
+    Punctuation,
+    Comment,
+}
+impl Class {
+    ///The class of tokens of `token_type`,`None` for the ones without text.
+    pub fn of(token_type: TokenType) -> Option<Self> {
+        use TokenType::*;
+        match token_type {
+            And | Class | Else | False | Fun | For | If | Nil | Or | Return | Super | This
+            | True | Var | While => Some(Self::Keyword),
+            String => Some(Self::String),
+            Number => Some(Self::Number),
+            Identifier => Some(Self::Identifier),
+            Minus | Plus | Slash | Star | Bang | BangEqual | Equal | EqualEqual | Greater
+            | GreaterEqual | Less | LessEqual => Some(Self::Operator),
+            LeftParen | RightParen | LeftBrace | RightBrace | Comma | Dot | Semicolon => {
+                Some(Self::Punctuation)
+            }
+            TokenError | Eof => None,
+        }
+    }
+    ///Name of the class in HTML,what `CSS` styles.
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Self::Keyword => "lox-keyword",
+            Self::String => "lox-string",
+            Self::Number => "lox-number",
+            Self::Identifier => "lox-identifier",
+            Self::Operator => "lox-operator",
+            Self::Punctuation => "lox-punctuation",
+            Self::Comment => "lox-comment",
+        }
+    }
+    ///The ANSI escape starting the colour of the class,`None` for text left as it is.
+    pub fn ansi(self) -> Option<&'static str> {
+        match self {
+            Self::Keyword => Some("\x1b[1;35m"),
+            Self::String => Some("\x1b[32m"),
+            Self::Number => Some("\x1b[33m"),
+            Self::Operator => Some("\x1b[36m"),
+            Self::Comment => Some("\x1b[90m"),
+            Self::Identifier | Self::Punctuation => None,
+        }
+    }
+}
+
+///A piece of the source,white space when it has no class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span<'a> {
+    pub class: Option<Class>,
+    pub text: &'a str,
+}
+
+///How `highlight` writes the code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Ansi,
+    ///A `<pre class="lox">` element with a `<span>` for each piece,styled by `CSS`.
+    Html,
+}
+
+///Colours for the classes of the HTML output,to put in a `<style>` element.
+pub const CSS: &str = "pre.lox { background: #fafafa; padding: 0.5em; }
+.lox-keyword { color: #a626a4; font-weight: bold; }
+.lox-string { color: #50a14f; }
+.lox-number { color: #986801; }
+.lox-operator { color: #0184bc; }
+.lox-comment { color: #a0a1a7; font-style: italic; }
+";
+
+///Split `code` into its pieces,in order.Put back together they are `code` again.Gives back the
+///scanner errors if it has text that isn't lox.
+pub fn spans<'a>(code: &'a str) -> Result<Vec<Span<'a>>, Box<dyn Error + 'a>> {
+    let tokens = Scanner::new(code.as_ascii_str()?).scan_tokens()?;
+    let mut output = Vec::new();
+    //Where the trivia of the next token starts in `code`.
+    let mut position = 0;
+    for x in &tokens {
+        position += x.trivia().len();
+        trivia(x.trivia(), &mut output);
+        //The lexeme of a string leaves out its quotes.
+        let length = match x.get_type() {
+            TokenType::String => x.as_str().len() + 2,
+            _ => x.as_str().len(),
+        };
+        if let Some(class) = Class::of(x.get_type()) {
+            output.push(Span {
+                class: Some(class),
+                text: &code[position..position + length],
+            });
+        }
+        position += length;
+    }
+    Ok(output)
+}
+///Split `trivia` into its white space and its comments.
+fn trivia<'a>(trivia: &'a str, output: &mut Vec<Span<'a>>) {
+    let mut rest = trivia;
+    //Only comments and white space are trivia,so a slash starts a comment.
+    while let Some(start) = rest.find('/') {
+        let end = rest[start..].find('\n').map_or(rest.len(), |x| start + x);
+        if start > 0 {
+            output.push(Span {
+                class: None,
+                text: &rest[..start],
+            });
+        }
+        output.push(Span {
+            class: Some(Class::Comment),
+            text: &rest[start..end],
+        });
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        output.push(Span {
+            class: None,
+            text: rest,
+        });
+    }
+}
+
+///Highlight `code` in `format`,giving back the scanner errors if it has text that isn't lox.
+pub fn highlight<'a>(code: &'a str, format: Format) -> Result<String, Box<dyn Error + 'a>> {
+    let spans = spans(code)?;
+    Ok(match format {
+        Format::Ansi => ansi(&spans),
+        Format::Html => html(&spans),
+    })
+}
+///The pieces with an ANSI colour each,for a terminal.
+pub fn ansi(spans: &[Span<'_>]) -> String {
+    let mut output = String::new();
+    for x in spans {
+        match x.class.and_then(Class::ansi) {
+            Some(colour) => {
+                output.push_str(colour);
+                output.push_str(x.text);
+                output.push_str("\x1b[0m");
+            }
+            None => output.push_str(x.text),
+        }
+    }
+    output
+}
+///The pieces as HTML,see `Format::Html`.
+pub fn html(spans: &[Span<'_>]) -> String {
+    let mut output = String::from("<pre class=\"lox\"><code>");
+    for x in spans {
+        match x.class {
+            Some(class) => {
+                output.push_str(&format!("<span class=\"{}\">", class.css_class()));
+                escape(x.text, &mut output);
+                output.push_str("</span>");
+            }
+            None => escape(x.text, &mut output),
+        }
+    }
+    output.push_str("</code></pre>\n");
+    output
+}
+///Escape the characters of `text` that mean something in HTML.
+pub(crate) fn escape(text: &str, output: &mut String) {
+    for x in text.chars() {
+        match x {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            x => output.push(x),
+        }
+    }
+}
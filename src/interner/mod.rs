@@ -0,0 +1,44 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
+
+///A cheap handle for an interned identifier.
+///Two symbols are equal only if they are made from the same string,so comparing and hashing
+///them never touches the string itself.
+///
+///The names live as long as the thread,so only identifiers are interned.There are few of them
+///and scripts share most,string values are never put here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+#[derive(Debug, Default)]
+struct Interner {
+    symbols: HashMap<Rc<str>, Symbol>,
+    names: Vec<Rc<str>>,
+}
+thread_local! {
+    static INTERNER: RefCell<Interner> = RefCell::default();
+}
+impl Interner {
+    fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(x) = self.symbols.get(name) {
+            return *x;
+        }
+        let symbol = Symbol(self.names.len() as u32);
+        let name: Rc<str> = name.into();
+        self.names.push(name.clone());
+        self.symbols.insert(name, symbol);
+        symbol
+    }
+}
+impl Symbol {
+    pub fn intern(name: &str) -> Self {
+        INTERNER.with(|x| x.borrow_mut().intern(name))
+    }
+    pub fn as_str(self) -> Rc<str> {
+        INTERNER.with(|x| x.borrow().names[self.0 as usize].clone())
+    }
+}
+impl Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.as_str(), f)
+    }
+}
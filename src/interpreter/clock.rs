@@ -0,0 +1,82 @@
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    rc::Rc,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use super::limits::{Budget, Interrupt};
+
+///How long a real sleep goes before it looks at the budget again.
+const SLEEP_SLICE: Duration = Duration::from_millis(50);
+
+///Where the time built-ins get the time from.
+///The host can give the interpreter its own clock,to freeze or move time in tests.
+pub trait Clock: Debug {
+    ///Seconds since the Unix epoch.
+    fn now(&self) -> f64;
+    fn sleep(&mut self, seconds: f64);
+    ///Sleep,stopping early when `budget` is cancelled or runs out of time.A clock that waits for
+    ///real should look at the budget while it waits.
+    fn sleep_within(&mut self, seconds: f64, budget: &Budget) -> Result<(), Interrupt> {
+        budget.check()?;
+        self.sleep(seconds);
+        budget.check()
+    }
+}
+
+///The real time of the machine.
+#[derive(Debug, Default)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> f64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(x) => x.as_secs_f64(),
+            Err(x) => -x.duration().as_secs_f64(),
+        }
+    }
+    fn sleep(&mut self, seconds: f64) {
+        thread::sleep(Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX));
+    }
+    fn sleep_within(&mut self, seconds: f64, budget: &Budget) -> Result<(), Interrupt> {
+        let start = Instant::now();
+        let length = Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX);
+        loop {
+            budget.check()?;
+            let left = length.saturating_sub(start.elapsed());
+            if left.is_zero() {
+                return Ok(());
+            }
+            thread::sleep(left.min(SLEEP_SLICE));
+        }
+    }
+}
+
+///A clock that only moves when told to.Sleeping moves it forward at once.
+///Clones share the same time,so the host keeps one to move the time the script sees.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<f64>>,
+}
+impl ManualClock {
+    pub fn new(now: f64) -> Self {
+        Self {
+            now: Rc::new(Cell::new(now)),
+        }
+    }
+    pub fn set(&self, now: f64) {
+        self.now.set(now);
+    }
+    pub fn advance(&self, seconds: f64) {
+        self.now.set(self.now.get() + seconds);
+    }
+}
+impl Clock for ManualClock {
+    fn now(&self) -> f64 {
+        self.now.get()
+    }
+    fn sleep(&mut self, seconds: f64) {
+        self.advance(seconds);
+    }
+}
@@ -0,0 +1,42 @@
+//!What debuggers and profilers see of a running script:a hook called before every statement,
+//!around every call of a lox function and when a variable is set,and the calls that haven't
+//!returned yet.
+use std::error::Error;
+
+use super::environment::{Environment, Scope};
+use crate::{interner::Symbol, lox_object::Values, token::Token};
+
+pub trait StatementHook<'a> {
+    ///Called before the statement on `line` runs in `env`.An error stops the script with it.
+    fn before_statement(
+        &mut self,
+        line: usize,
+        env: &Environment<'a>,
+    ) -> Result<(), Box<dyn Error>>;
+    ///Called when a lox function starts,its frame is the last one of `env`.
+    fn call(&mut self, _arguments: &[Values<'a>], _env: &Environment<'a>) {}
+    ///Called when a lox function ends,while its frame is still the last one of `env`.`value` is
+    ///what it gives back,`None` when it ended with an error.
+    fn returned(&mut self, _value: Option<&Values<'a>>, _env: &Environment<'a>) {}
+    ///Called when the script picks a way at `at`,the keyword of an `if` or a loop or the
+    ///operator of `and`/`or`.`arm` is 0 for the `then` branch,another turn of the loop or a
+    ///short-circuit,and 1 for the `else` branch,the end of the loop or evaluating the right side.
+    fn branch(&mut self, _at: Token<'a>, _arm: usize, _env: &Environment<'a>) {}
+    ///Called when `name` was declared with `value` in the current scope of `env`,parameters
+    ///included.
+    fn defined(&mut self, _name: Symbol, _value: &Values<'a>, _env: &Environment<'a>) {}
+    ///Called when `value` was assigned to the variable `name` that was declared before.
+    fn redefined(&mut self, _name: Symbol, _value: &Values<'a>, _env: &Environment<'a>) {}
+}
+
+///A call of a lox function that hasn't returned yet.
+#[derive(Debug, Clone)]
+pub struct Frame<'a> {
+    pub function: Token<'a>,
+    ///Line of the call,where the caller is stopped.
+    pub call_line: usize,
+    ///Scope of the caller,it is the current scope again after the return.
+    pub caller: Scope<'a>,
+    ///Scope the function was declared in.
+    pub closure: Scope<'a>,
+}
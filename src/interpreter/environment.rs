@@ -1,22 +1,42 @@
 use std::{
+    cell::RefCell,
     collections::HashMap,
+    error::Error,
     fmt::Debug,
     io::Write,
-    mem,
+    mem::{self, size_of},
     rc::Rc,
 };
 
+use super::{
+    clock::{Clock, SystemClock},
+    debug::{Frame, StatementHook},
+    limits::{Budget, Interrupt},
+    memory::Heap,
+    process::Process,
+    random::Rng,
+};
 use crate::{
+    ast::statement::Stmt,
     basic_function::RcRef,
+    interner::Symbol,
     lox_object::{
-        builtinfunction::{ClockFunc, PrintFunc},
+        builtinfunction::{self, math},
         Values,
     },
+    token::Token,
 };
 
+///How a value is stored inside a scope.
+#[cfg(not(feature = "nan-boxing"))]
+pub type Slot<'a> = Values<'a>;
+///How a value is stored inside a scope.
+#[cfg(feature = "nan-boxing")]
+pub type Slot<'a> = crate::lox_object::nanbox::NanBox<'a>;
+
 #[derive(Default, Debug, Clone)]
 pub struct Scopedata<'a> {
-    values: HashMap<String, Values<'a>>,
+    values: HashMap<Symbol, Slot<'a>>,
     parent: Option<Scope<'a>>,
 }
 #[derive(Debug, Clone, Default)]
@@ -29,10 +49,10 @@ impl<'a> Scope<'a> {
     fn delete_sub_values(&mut self) {
         *self = self.0.clone().brw().parent.clone().unwrap();
     }
-    pub fn get(&self, key: &str) -> Option<Values<'a>> {
+    pub fn get(&self, key: Symbol) -> Option<Values<'a>> {
         let brw = self.0.brw();
-        match brw.values.get(key) {
-            Some(x) => Some(x.clone()),
+        match brw.values.get(&key) {
+            Some(x) => Some(x.into()),
             None => match &brw.parent {
                 Some(x) => x.get(key),
                 None => None,
@@ -40,13 +60,16 @@ impl<'a> Scope<'a> {
         }
     }
 
-    pub fn define(&mut self, key: String, value: Values<'a>) {
-        self.0.brw_mut().values.insert(key, value);
+    //The conversion does nothing unless the values are nan-boxed.
+    #[allow(clippy::useless_conversion)]
+    pub fn define(&mut self, key: Symbol, value: Values<'a>) {
+        self.0.brw_mut().values.insert(key, value.into());
     }
-    pub fn redefine(&mut self, key: &str, value: Values<'a>) -> Result<(), String> {
+    #[allow(clippy::useless_conversion)]
+    pub fn redefine(&mut self, key: Symbol, value: Values<'a>) -> Result<(), String> {
         let mut brw_mut=self.0.brw_mut();
-        if  let Some(x)=brw_mut.values.get_mut(key){
-            *x=value;
+        if  let Some(x)=brw_mut.values.get_mut(&key){
+            *x=value.into();
         }
         else if let Some(x)=brw_mut.parent.as_mut(){
             x.redefine(key,value)?;
@@ -54,13 +77,59 @@ impl<'a> Scope<'a> {
         else{return Err(format!("Variable {} not defined",key));}
         Ok(())
     }
-    pub fn contain(&self, key: &str) -> bool {
+    pub fn contain(&self, key: Symbol) -> bool {
         self.get(key).is_some()
     }
+    pub fn inner(&self) -> &Rc<RefCell<Scopedata<'a>>> {
+        self.0.inner()
+    }
+    ///The scope this one was opened in.
+    pub fn parent(&self) -> Option<Scope<'a>> {
+        self.0.brw().parent.clone()
+    }
+    ///The variables defined in this scope,without the ones of its parents.
+    pub fn variables(&self) -> Vec<(Symbol, Values<'a>)> {
+        let brw = self.0.brw();
+        brw.values.iter().map(|(k, v)| (*k, v.into())).collect()
+    }
+    pub fn same(&self, other: &Scope<'a>) -> bool {
+        Rc::ptr_eq(self.inner(), other.inner())
+    }
+}
+impl Scopedata<'_> {
+    ///Approximate bytes used by the scope and the values defined in it.
+    pub fn heap_size(&self) -> usize {
+        let values: usize = self
+            .values
+            .values()
+            .map(|value| size_of::<Symbol>() + size_of::<Slot>() + Values::from(value).heap_size())
+            .sum();
+        size_of::<Self>() + values
+    }
+    ///Addresses of the scopes and closures kept alive by this scope,for the garbage collector.
+    pub fn references(&self, output: &mut Vec<usize>) {
+        if let Some(x) = &self.parent {
+            output.push(Rc::as_ptr(x.inner()) as *const () as usize);
+        }
+        for x in self.values.values() {
+            Values::from(x).references(output);
+        }
+    }
 }
 pub struct Environment<'a> {
     scope: Scope<'a>,
     stdout: &'a mut dyn Write,
+    budget: Budget,
+    heap: Heap<'a>,
+    filesystem: bool,
+    process: Process,
+    rng: Rng,
+    clock: Box<dyn Clock>,
+    call_line: usize,
+    ///Scope of the built-ins,the outermost one a script sees.
+    builtins: Scope<'a>,
+    hook: Option<Box<dyn StatementHook<'a> + 'a>>,
+    frames: Vec<Frame<'a>>,
 }
 impl Debug for Environment<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -72,22 +141,48 @@ impl<'a> Environment<'a> {
         let mut output = Self {
             scope: Scope::default(),
             stdout,
+            budget: Budget::default(),
+            heap: Heap::default(),
+            filesystem: false,
+            process: Process::default(),
+            rng: Rng::default(),
+            clock: Box::new(SystemClock),
+            call_line: 0,
+            builtins: Scope::default(),
+            hook: None,
+            frames: Vec::new(),
         };
+        output.heap.track_scope(&output.scope);
         output.include_globals();
         output
     }
     pub fn create_sub_values(&mut self) {
         self.scope.create_sub_values();
+        self.heap.track_scope(&self.scope);
     }
     pub fn delete_sub_values(&mut self) {
         self.scope.delete_sub_values();
     }
 
-    pub fn define(&mut self, name: String, value: Values<'a>) {
-        self.scope.define(name, value);
+    pub fn define(&mut self, name: Symbol, value: Values<'a>) {
+        let Some(mut hook) = self.hook.take() else {
+            self.scope.define(name, value);
+            return;
+        };
+        self.scope.define(name, value.clone());
+        hook.defined(name, &value, self);
+        self.hook = Some(hook);
     }
-    pub fn redefine(&mut self, name: &str, value: Values<'a>) -> Result<(), String> {
-        self.scope.redefine(name, value)
+    pub fn redefine(&mut self, name: Symbol, value: Values<'a>) -> Result<(), String> {
+        let Some(mut hook) = self.hook.take() else {
+            return self.scope.redefine(name, value);
+        };
+        let result = self.scope.redefine(name, value.clone());
+        if result.is_ok() {
+            hook.redefined(name, &value, self);
+        }
+        self.hook = Some(hook);
+        result
     }
     pub fn get_current(&self) -> Scope<'a> {
         self.scope.clone()
@@ -101,19 +196,122 @@ impl<'a> Environment<'a> {
         self.stdout.write_all(b"\n")?;
         Ok(())
     }
+    pub fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.stdout.flush()
+    }
 
     fn include_globals(&mut self) {
         self.create_sub_values();
-        self.define("print".to_owned(), Values::<'a>::Fn(Rc::new(PrintFunc)));
-        let clock_timer = ClockFunc::new();
-        self.define("clock".to_owned(), Values::Fn(Rc::new(clock_timer)));
+        for x in builtinfunction::functions() {
+            self.define(Symbol::intern(x.name()), Values::Fn(x));
+        }
+        for (name, value) in math::CONSTANTS {
+            self.define(Symbol::intern(name), Values::Number(value));
+        }
+        self.builtins = self.scope.clone();
     }
 
-    pub fn get(&self, key: &str) -> Option<Values<'a>> {
+    pub fn get(&self, key: Symbol) -> Option<Values<'a>> {
         self.scope.get(key)
     }
 
     pub fn set_scope(&mut self, values: Scope<'a>) {
         self.scope = values;
     }
+    ///Count one step of execution against the budget.
+    pub fn step(&mut self) -> Result<(), Interrupt> {
+        self.budget.step()?;
+        self.heap.check()
+    }
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+    pub fn budget_mut(&mut self) -> &mut Budget {
+        &mut self.budget
+    }
+    pub fn heap_mut(&mut self) -> &mut Heap<'a> {
+        &mut self.heap
+    }
+    ///If the file built-ins may touch the filesystem.
+    pub fn filesystem_allowed(&self) -> bool {
+        self.filesystem
+    }
+    pub fn set_filesystem_allowed(&mut self, allowed: bool) {
+        self.filesystem = allowed;
+    }
+    pub fn process(&self) -> &Process {
+        &self.process
+    }
+    pub fn process_mut(&mut self) -> &mut Process {
+        &mut self.process
+    }
+    pub fn rng_mut(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+    pub fn clock_mut(&mut self) -> &mut dyn Clock {
+        self.clock.as_mut()
+    }
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+    ///Sleep on the clock,stopped by the budget like a loop would be.
+    pub fn sleep(&mut self, seconds: f64) -> Result<(), Interrupt> {
+        self.clock.sleep_within(seconds, &self.budget)
+    }
+    ///Line of the last function call,so a built-in can tell where it was called from.
+    pub fn call_line(&self) -> usize {
+        self.call_line
+    }
+    pub fn set_call_line(&mut self, line: usize) {
+        self.call_line = line;
+    }
+    pub fn builtins(&self) -> &Scope<'a> {
+        &self.builtins
+    }
+    pub fn set_statement_hook(&mut self, hook: Option<Box<dyn StatementHook<'a> + 'a>>) {
+        self.hook = hook;
+    }
+    ///Let the statement hook see `statement` before it runs.
+    pub fn before_statement(&mut self, statement: &dyn Stmt<'a>) -> Result<(), Box<dyn Error>> {
+        let Some(mut hook) = self.hook.take() else {
+            return Ok(());
+        };
+        let result = match statement.line() {
+            Some(line) => hook.before_statement(line, self),
+            None => Ok(()),
+        };
+        self.hook = Some(hook);
+        result
+    }
+    ///Let the hook see which `arm` of the branch at `at` the script took,see
+    ///`StatementHook::branch`.
+    pub fn branch(&mut self, at: Token<'a>, arm: usize) {
+        if let Some(mut hook) = self.hook.take() {
+            hook.branch(at, arm, self);
+            self.hook = Some(hook);
+        }
+    }
+    ///The calls in progress,the innermost last.
+    pub fn frames(&self) -> &[Frame<'a>] {
+        &self.frames
+    }
+    ///Start the call of `frame`,the hook sees it with its `arguments`.
+    pub fn push_frame(&mut self, frame: Frame<'a>, arguments: &[Values<'a>]) {
+        self.frames.push(frame);
+        if let Some(mut hook) = self.hook.take() {
+            hook.call(arguments, self);
+            self.hook = Some(hook);
+        }
+    }
+    ///End the innermost call,that gave back `value` or `None` on an error.
+    pub fn pop_frame(&mut self, value: Option<&Values<'a>>) {
+        if let Some(mut hook) = self.hook.take() {
+            hook.returned(value, self);
+            self.hook = Some(hook);
+        }
+        self.frames.pop();
+    }
 }
@@ -0,0 +1,122 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+///The reason the interpreter stopped a script before it finished.
+///It is returned as the runtime error,so a host can downcast it and tell it apart from an error
+///in the script itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interrupt {
+    StepLimit(u64),
+    Timeout(Duration),
+    Cancelled,
+    OutOfMemory(usize),
+    CallDepth(usize),
+}
+impl Display for Interrupt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StepLimit(x) => write!(f, "Execution stopped:step budget of {} exhausted.", x),
+            Self::Timeout(x) => write!(f, "Execution stopped:timed out after {:?}.", x),
+            Self::Cancelled => write!(f, "Execution stopped:cancelled by the host."),
+            Self::OutOfMemory(x) => write!(f, "Out of memory:script used more than {} bytes.", x),
+            Self::CallDepth(x) => write!(f, "Execution stopped:more than {} nested calls.", x),
+        }
+    }
+}
+impl Error for Interrupt {}
+
+///Nested calls allowed by default.Each one takes a few KB of the stack of the thread running the
+///script,this fits in the 2MB of a spawned thread even in a debug build.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+///Keep track of how much work a script is allowed to do.
+///A step is counted on every loop iteration and every function call.
+#[derive(Debug)]
+pub struct Budget {
+    max_steps: Option<u64>,
+    timeout: Option<Duration>,
+    max_depth: Option<usize>,
+    steps: u64,
+    depth: usize,
+    deadline: Option<Instant>,
+    cancelled: Arc<AtomicBool>,
+}
+impl Default for Budget {
+    fn default() -> Self {
+        Self {
+            max_steps: None,
+            timeout: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            steps: 0,
+            depth: 0,
+            deadline: None,
+            cancelled: Arc::default(),
+        }
+    }
+}
+impl Budget {
+    pub fn set_max_steps(&mut self, max_steps: Option<u64>) {
+        self.max_steps = max_steps;
+    }
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+    ///The flag is never cleared by the interpreter,the host has to store `false` in it to run
+    ///again after cancelling.
+    pub fn cancel_handle(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+    ///Reset the step counter and start the clock for a new run.
+    pub fn start(&mut self) {
+        self.steps = 0;
+        self.depth = 0;
+        self.deadline = self.timeout.map(|x| Instant::now() + x);
+    }
+    pub fn step(&mut self) -> Result<(), Interrupt> {
+        self.steps += 1;
+        if let Some(max_steps) = self.max_steps {
+            if self.steps > max_steps {
+                return Err(Interrupt::StepLimit(max_steps));
+            }
+        }
+        self.check()
+    }
+    ///Go one call deeper,every successful `enter` is paired with a `leave`.
+    pub fn enter(&mut self) -> Result<(), Interrupt> {
+        if let Some(max_depth) = self.max_depth {
+            if self.depth >= max_depth {
+                return Err(Interrupt::CallDepth(max_depth));
+            }
+        }
+        self.depth += 1;
+        Ok(())
+    }
+    pub fn leave(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+    ///Whether the script was cancelled or ran out of time,without counting a step.
+    pub fn check(&self) -> Result<(), Interrupt> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(Interrupt::Cancelled);
+        }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.timeout) {
+            if Instant::now() >= deadline {
+                return Err(Interrupt::Timeout(timeout));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    mem::{self, size_of},
+    rc::{Rc, Weak},
+};
+
+use super::{
+    environment::{Scope, Scopedata},
+    limits::Interrupt,
+};
+use crate::lox_object::LoxFunction;
+
+///How many bytes can be allocated before the live memory is measured again,when no limit is
+///forcing it earlier.
+const MEASURE_INTERVAL: usize = 64 * 1024;
+
+///Approximate accounting of the memory held by scopes,closures and the strings inside them.
+///Scopes and closures are kept here as weak reference,so whatever is dropped stop counting
+///the next time the memory is measured.
+///
+///A closure keeps the scope it was declared in,and that scope keeps the closure,so `Rc` alone
+///never frees them.`collect` finds such cycles and breaks them.
+#[derive(Debug, Default)]
+pub struct Heap<'a> {
+    scopes: Vec<Weak<RefCell<Scopedata<'a>>>>,
+    functions: Vec<Weak<LoxFunction<'a>>>,
+    limit: Option<usize>,
+    in_use: usize,
+    pending: usize,
+    peak: usize,
+    ///Estimate of the bytes in use at which the memory is measured again.
+    next_measure: usize,
+    collections: u64,
+}
+impl<'a> Heap<'a> {
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+        self.schedule();
+    }
+    pub fn track_scope(&mut self, scope: &Scope<'a>) {
+        self.scopes.push(Rc::downgrade(scope.inner()));
+        self.pending += size_of::<Scopedata>();
+    }
+    pub fn track_function(&mut self, function: &Rc<LoxFunction<'a>>) {
+        self.functions.push(Rc::downgrade(function));
+        self.pending += function.heap_size();
+    }
+    ///Account for `bytes` more memory,failing when it doesn't fit in the limit.
+    pub fn allocate(&mut self, bytes: usize) -> Result<(), Interrupt> {
+        self.pending = self.pending.saturating_add(bytes);
+        if !self.over_limit() {
+            return Ok(());
+        }
+        self.collect();
+        //The new allocation isn't reachable from any scope yet,so it is added on top.
+        let live = self.measure().saturating_add(bytes);
+        self.peak = self.peak.max(live);
+        match self.limit {
+            Some(limit) if live > limit => Err(Interrupt::OutOfMemory(limit)),
+            _ => Ok(()),
+        }
+    }
+    ///Check the memory allocated so far against the limit.
+    pub fn check(&mut self) -> Result<(), Interrupt> {
+        self.allocate(0)
+    }
+    pub fn in_use(&mut self) -> usize {
+        self.measure()
+    }
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+    ///How many times `collect` ran.
+    pub fn collections(&self) -> u64 {
+        self.collections
+    }
+    ///Bytes that can still be allocated under the limit,`None` when there is no limit.
+    pub fn remaining(&mut self) -> Option<usize> {
+        let limit = self.limit?;
+        Some(limit.saturating_sub(self.measure()))
+    }
+
+    ///Free the scopes and closures that are only alive because they point to each other.
+    ///Returns how many objects were freed.
+    ///
+    ///Anything whose reference count is higher than the references coming from other tracked
+    ///objects is held from outside,by the interpreter itself or a value on the rust stack.
+    ///Everything reachable from those is alive,the rest is garbage.
+    pub fn collect(&mut self) -> usize {
+        self.collections += 1;
+        let scopes: Vec<_> = self.scopes.iter().filter_map(Weak::upgrade).collect();
+        let functions: Vec<_> = self.functions.iter().filter_map(Weak::upgrade).collect();
+        let mut index = HashMap::new();
+        for (i, x) in scopes.iter().enumerate() {
+            index.insert(Rc::as_ptr(x) as *const () as usize, i);
+        }
+        for (i, x) in functions.iter().enumerate() {
+            index.insert(Rc::as_ptr(x) as *const () as usize, scopes.len() + i);
+        }
+        let node_count = scopes.len() + functions.len();
+        let mut edges = vec![Vec::new(); node_count];
+        let mut internal = vec![0; node_count];
+        let mut alive = vec![false; node_count];
+        let mut references = Vec::new();
+        for (i, x) in scopes.iter().enumerate() {
+            let Ok(data) = x.try_borrow() else {
+                //Someone is using it right now.
+                alive[i] = true;
+                continue;
+            };
+            data.references(&mut references);
+            edges[i].extend(references.drain(..).filter_map(|x| index.get(&x).copied()));
+        }
+        for (i, x) in functions.iter().enumerate() {
+            let scope = Rc::as_ptr(x.scope().inner()) as *const () as usize;
+            edges[scopes.len() + i].extend(index.get(&scope).copied());
+        }
+        for x in edges.iter().flatten() {
+            internal[*x] += 1;
+        }
+        //One reference of each object is the one taken above for the collection.
+        let strong_count = |i: usize| match i.checked_sub(scopes.len()) {
+            Some(i) => Rc::strong_count(&functions[i]) - 1,
+            None => Rc::strong_count(&scopes[i]) - 1,
+        };
+        let mut stack: Vec<usize> = (0..node_count)
+            .filter(|x| alive[*x] || strong_count(*x) > internal[*x])
+            .collect();
+        while let Some(x) = stack.pop() {
+            alive[x] = true;
+            stack.extend(edges[x].iter().filter(|x| !alive[**x]));
+        }
+        let freed = alive.iter().filter(|x| !**x).count();
+        let garbage: Vec<_> = scopes
+            .iter()
+            .zip(alive)
+            .filter(|(_, alive)| !alive)
+            .map(|(x, _)| mem::take(&mut *x.borrow_mut()))
+            .collect();
+        //Dropping the garbage may drop other scopes,so it is done after every borrow ended.
+        drop(garbage);
+        freed
+    }
+
+    fn over_limit(&self) -> bool {
+        self.in_use.saturating_add(self.pending) > self.next_measure
+    }
+    ///Pick when to measure again.Under a limit it is when the limit is reached,but close to it
+    ///not before a quarter more than what is live was allocated,so a script running near the
+    ///limit doesn't collect on every allocation.It may go that much over the limit before it is
+    ///stopped.
+    fn schedule(&mut self) {
+        let growth = match self.limit {
+            Some(limit) => limit.saturating_sub(self.in_use).max(self.in_use / 4),
+            None => MEASURE_INTERVAL,
+        };
+        self.next_measure = self.in_use.saturating_add(growth);
+    }
+    fn measure(&mut self) -> usize {
+        self.scopes.retain(|x| x.strong_count() > 0);
+        self.functions.retain(|x| x.strong_count() > 0);
+        let scopes = self.scopes.iter().filter_map(Weak::upgrade);
+        let scope_bytes: usize = scopes
+            .map(|x| x.try_borrow().map_or(0, |x| x.heap_size()))
+            .sum();
+        let function_bytes: usize = self
+            .functions
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|x| x.heap_size())
+            .sum();
+        self.in_use = scope_bytes + function_bytes;
+        self.pending = 0;
+        self.peak = self.peak.max(self.in_use);
+        self.schedule();
+        self.in_use
+    }
+}
@@ -1,11 +1,39 @@
 #[macro_use]
 pub mod environment;
+pub mod clock;
+pub mod debug;
+pub mod limits;
+pub mod memory;
+pub mod process;
+pub mod random;
 
-use std::{io::Write, ops::Deref};
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::Write,
+    ops::Deref,
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
 
+use clock::Clock;
+use debug::StatementHook;
 use environment::Environment;
+use process::Exit;
 
 use crate::{ast::statement::Stmt, lox_error::emit_error};
+///Numbers about the last run,for the host to monitor.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats{
+    ///Approximate bytes held by scopes,closures and strings right now.
+    pub bytes_in_use:usize,
+    ///The most bytes seen in use since the interpreter was created.
+    pub peak_bytes:usize,
+    ///Loop iterations and function calls counted in the last run.
+    pub steps:u64,
+    ///Garbage collections run since the interpreter was created.
+    pub collections:u64,
+}
 #[derive(Debug)]
 pub struct Interpreter<'input>{
     env:Environment<'input>,
@@ -17,12 +45,92 @@ impl<'a> Interpreter<'a>{
         }
     }
     pub fn interpret(&mut self, statement:& dyn Stmt<'a>){
-        if let Err(x)=statement.execute(&mut self.env){
-            emit_error(x.deref());
+        if let Err(x)=self.try_interpret(statement){
+            //`exit` is how the script asked to stop,not an error to report.
+            if !x.is::<Exit>(){
+                emit_error(x.deref());
+            }
+        }
+    }
+    ///Same as interpret,but the runtime error is given back to the caller instead of being
+    ///printed.Errors from the budget can be told apart by downcasting to `limits::Interrupt`.
+    pub fn try_interpret(&mut self, statement:& dyn Stmt<'a>)->Result<(),Box<dyn Error>>{
+        let scope=self.env.get_current();
+        self.env.budget_mut().start();
+        let result=statement.execute(&mut self.env);
+        if result.is_err(){
+            //The error may come from inside a function or a block,so the scope is put back to
+            //where it was before the run.
+            self.env.set_scope(scope);
         }
+        result.map(|_|())
     }
     ///Setup the environment espicially for repl,
     pub fn repl_mode(&mut self){
         self.env.create_sub_values();
     }
+    ///Maximum number of loop iterations and function calls for one run.`None` means no limit.
+    pub fn set_step_limit(&mut self, max_steps:Option<u64>){
+        self.env.budget_mut().set_max_steps(max_steps);
+    }
+    ///Wall clock time one run is allowed to take.`None` means no limit.
+    pub fn set_timeout(&mut self, timeout:Option<Duration>){
+        self.env.budget_mut().set_timeout(timeout);
+    }
+    ///How deep calls may nest before the run stops,so deep recursion is an error and not a stack
+    ///overflow.`limits::DEFAULT_MAX_DEPTH` by default,`None` means no limit.
+    pub fn set_max_call_depth(&mut self, max_depth:Option<usize>){
+        self.env.budget_mut().set_max_depth(max_depth);
+    }
+    ///Setting the flag to `true` from any thread stops the running script.
+    pub fn cancel_handle(&self)->Arc<AtomicBool>{
+        self.env.budget().cancel_handle()
+    }
+    ///Approximate number of bytes the script may keep alive.`None` means no limit.
+    ///Near the limit it is checked less often,so a script can get a quarter over what it keeps
+    ///alive before it stops.
+    pub fn set_memory_limit(&mut self, limit:Option<usize>){
+        self.env.heap_mut().set_limit(limit);
+    }
+    ///Allow or forbid the file built-ins,they fail with a runtime error when forbidden.
+    ///Forbidden by default,the host opts in.
+    pub fn set_filesystem_access(&mut self, allowed:bool){
+        self.env.set_filesystem_allowed(allowed);
+    }
+    ///Values given back by `args()`,the arguments after the script name.
+    pub fn set_args(&mut self, args:Vec<String>){
+        self.env.process_mut().set_args(args);
+    }
+    ///Variables `getenv` reads from.`None` reads the environment of the real process,by default
+    ///there are none so a script can't read the secrets of the host.
+    pub fn set_env_vars(&mut self, vars:Option<HashMap<String,String>>){
+        self.env.process_mut().set_vars(vars);
+    }
+    ///Restart the random built-ins from `seed`,the same seed always gives the same sequence.
+    pub fn set_seed(&mut self, seed:u64){
+        self.env.rng_mut().seed(seed);
+    }
+    ///Clock read by `now` and `sleep`,a `clock::ManualClock` makes them deterministic.
+    pub fn set_clock(&mut self, clock:Box<dyn Clock>){
+        self.env.set_clock(clock);
+    }
+    ///Hook called before every statement,the way debuggers follow the script.`None` removes it.
+    pub fn set_statement_hook(&mut self, hook:Option<Box<dyn StatementHook<'a>+'a>>){
+        self.env.set_statement_hook(hook);
+    }
+    ///Free the scopes and closures only kept alive by reference cycles,returns how many objects
+    ///were freed.
+    pub fn gc(&mut self)->usize{
+        self.env.heap_mut().collect()
+    }
+    pub fn stats(&mut self)->Stats{
+        let heap=self.env.heap_mut();
+        let bytes_in_use=heap.in_use();
+        Stats{
+            bytes_in_use,
+            peak_bytes:heap.peak(),
+            collections:heap.collections(),
+            steps:self.env.budget().steps(),
+        }
+    }
 }
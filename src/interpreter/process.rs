@@ -0,0 +1,46 @@
+use std::{collections::HashMap, error::Error, fmt::Display};
+
+///Returned as the runtime error by the `exit` built-in.
+///The host downcasts it to get the exit code,the output was already flushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exit(pub i32);
+impl Display for Exit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Script exited with code {}.", self.0)
+    }
+}
+impl Error for Exit {}
+
+///What the script sees of the process running it.
+#[derive(Debug)]
+pub struct Process {
+    args: Vec<String>,
+    vars: Option<HashMap<String, String>>,
+}
+impl Default for Process {
+    ///No arguments and no environment variables,the host has to hand the real ones over.
+    fn default() -> Self {
+        Self {
+            args: Vec::new(),
+            vars: Some(HashMap::new()),
+        }
+    }
+}
+impl Process {
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+    ///`None` reads the environment variables of the real process.
+    pub fn set_vars(&mut self, vars: Option<HashMap<String, String>>) {
+        self.vars = vars;
+    }
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+    pub fn var(&self, name: &str) -> Option<String> {
+        match &self.vars {
+            Some(x) => x.get(name).cloned(),
+            None => std::env::var(name).ok(),
+        }
+    }
+}
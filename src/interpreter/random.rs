@@ -0,0 +1,47 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+///SplitMix64,small and fast,and the same sequence for a seed on every platform.
+///Not good enough for anything that has to be secret.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+impl Default for Rng {
+    ///Seeded from the clock,call `seed` for a sequence that can be repeated.
+    fn default() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_nanos() as u64);
+        Self::new(nanos)
+    }
+}
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+    pub fn seed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut x = self.state;
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x ^ (x >> 31)
+    }
+    ///A number in `[0,1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+    ///A number in `[0,bound)`,without the bias of a plain modulo.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        //Values past the last whole multiple of `bound` would make the low results likelier.
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % bound;
+            }
+        }
+    }
+}
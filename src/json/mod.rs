@@ -0,0 +1,409 @@
+use std::{error::Error, fmt::Display};
+
+///A JSON document.Objects keep their keys in the order they were written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+impl Json {
+    ///An object from its pairs,in order.
+    pub fn object<'k>(pairs: impl IntoIterator<Item = (&'k str, Json)>) -> Self {
+        Json::Object(pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+    ///The value of `key`,if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(x) => x.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(x) => Some(x),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(x) => Some(*x),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(x) => Some(*x),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(x) => Some(x),
+            _ => None,
+        }
+    }
+    ///Written over several lines,indented by two spaces for each level.
+    pub fn to_pretty_string(&self) -> String {
+        let mut output = String::new();
+        self.write(&mut output, Some(0));
+        output
+    }
+    ///`indent` is the current level when pretty printing,`None` writes everything in one line.
+    fn write(&self, output: &mut String, indent: Option<usize>) {
+        let newline = |output: &mut String, level: usize| {
+            output.push('\n');
+            output.push_str(&"  ".repeat(level));
+        };
+        match self {
+            Json::Null => output.push_str("null"),
+            Json::Bool(x) => output.push_str(if *x { "true" } else { "false" }),
+            Json::Number(x) => output.push_str(&x.to_string()),
+            Json::Str(x) => write_string(output, x),
+            Json::Array(x) if x.is_empty() => output.push_str("[]"),
+            Json::Object(x) if x.is_empty() => output.push_str("{}"),
+            Json::Array(x) => {
+                output.push('[');
+                for (i, value) in x.iter().enumerate() {
+                    if i != 0 {
+                        output.push(',');
+                    }
+                    if let Some(level) = indent {
+                        newline(output, level + 1);
+                    }
+                    value.write(output, indent.map(|x| x + 1));
+                }
+                if let Some(level) = indent {
+                    newline(output, level);
+                }
+                output.push(']');
+            }
+            Json::Object(x) => {
+                output.push('{');
+                for (i, (key, value)) in x.iter().enumerate() {
+                    if i != 0 {
+                        output.push(',');
+                    }
+                    if let Some(level) = indent {
+                        newline(output, level + 1);
+                    }
+                    write_string(output, key);
+                    output.push(':');
+                    if indent.is_some() {
+                        output.push(' ');
+                    }
+                    value.write(output, indent.map(|x| x + 1));
+                }
+                if let Some(level) = indent {
+                    newline(output, level);
+                }
+                output.push('}');
+            }
+        }
+    }
+}
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::Str(value.to_string())
+    }
+}
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::Str(value)
+    }
+}
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        Json::Number(value)
+    }
+}
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Number(value as f64)
+    }
+}
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+impl From<Vec<Json>> for Json {
+    fn from(value: Vec<Json>) -> Self {
+        Json::Array(value)
+    }
+}
+///Compact,in one line.
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut output = String::new();
+        self.write(&mut output, None);
+        f.write_str(&output)
+    }
+}
+fn write_string(output: &mut String, text: &str) {
+    output.push('"');
+    for x in text.chars() {
+        match x {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            x if (x as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", x as u32)),
+            x => output.push(x),
+        }
+    }
+    output.push('"');
+}
+
+///Where and why a document couldn't be read.Lines and columns start at 1,columns count
+///characters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at line {},column {}.",
+            self.message, self.line, self.column
+        )
+    }
+}
+impl Error for ParseError {}
+
+///Read a whole document,anything but white space after the value is an error.
+pub fn parse(text: &str) -> Result<Json, ParseError> {
+    let mut parser = Parser { text, position: 0 };
+    let value = parser.value(0)?;
+    parser.skip_space();
+    if parser.position != text.len() {
+        return Err(parser.error("Unexpected text after the value"));
+    }
+    Ok(value)
+}
+
+///Deeper documents are refused instead of overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+struct Parser<'a> {
+    text: &'a str,
+    ///Byte offset of the next character.
+    position: usize,
+}
+impl Parser<'_> {
+    fn error(&self, message: &str) -> ParseError {
+        let before = &self.text[..self.position];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+        ParseError {
+            line,
+            column: before[line_start..].chars().count() + 1,
+            message: message.to_string(),
+        }
+    }
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+    fn bump(&mut self) -> Option<char> {
+        let x = self.peek()?;
+        self.position += x.len_utf8();
+        Some(x)
+    }
+    fn skip_space(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+    fn expect(&mut self, word: &str, value: Json) -> Result<Json, ParseError> {
+        if !self.text[self.position..].starts_with(word) {
+            return Err(self.error("Unexpected character"));
+        }
+        self.position += word.len();
+        Ok(value)
+    }
+    fn value(&mut self, depth: usize) -> Result<Json, ParseError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("Too deeply nested"));
+        }
+        self.skip_space();
+        match self.peek() {
+            None => Err(self.error("Unexpected end of the text")),
+            Some('n') => self.expect("null", Json::Null),
+            Some('t') => self.expect("true", Json::Bool(true)),
+            Some('f') => self.expect("false", Json::Bool(false)),
+            Some('"') => Ok(Json::Str(self.string()?)),
+            Some('[') => self.array(depth),
+            Some('{') => self.object(depth),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) => Err(self.error("Unexpected character")),
+        }
+    }
+    fn array(&mut self, depth: usize) -> Result<Json, ParseError> {
+        self.position += 1;
+        let mut values = Vec::new();
+        self.skip_space();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value(depth + 1)?);
+            self.skip_space();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => {
+                    self.position += 1;
+                    return Ok(Json::Array(values));
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+    fn object(&mut self, depth: usize) -> Result<Json, ParseError> {
+        self.position += 1;
+        let mut values = Vec::new();
+        self.skip_space();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Json::Object(values));
+        }
+        loop {
+            self.skip_space();
+            if self.peek() != Some('"') {
+                return Err(self.error("Expected a string key"));
+            }
+            let key = self.string()?;
+            self.skip_space();
+            if self.peek() != Some(':') {
+                return Err(self.error("Expected ':'"));
+            }
+            self.position += 1;
+            values.push((key, self.value(depth + 1)?));
+            self.skip_space();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some('}') => {
+                    self.position += 1;
+                    return Ok(Json::Object(values));
+                }
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+    fn number(&mut self) -> Result<Json, ParseError> {
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            let from = parser.position;
+            while let Some('0'..='9') = parser.peek() {
+                parser.position += 1;
+            }
+            parser.position > from
+        };
+        if self.peek() == Some('-') {
+            self.position += 1;
+        }
+        //A leading zero can't be followed by more digits.
+        if self.peek() == Some('0') {
+            self.position += 1;
+            if let Some('0'..='9') = self.peek() {
+                return Err(self.error("Invalid number"));
+            }
+        } else if !digits(self) {
+            return Err(self.error("Invalid number"));
+        }
+        if self.peek() == Some('.') {
+            self.position += 1;
+            if !digits(self) {
+                return Err(self.error("Invalid number"));
+            }
+        }
+        if let Some('e' | 'E') = self.peek() {
+            self.position += 1;
+            if let Some('+' | '-') = self.peek() {
+                self.position += 1;
+            }
+            if !digits(self) {
+                return Err(self.error("Invalid number"));
+            }
+        }
+        let number = self.text[start..self.position].parse().unwrap();
+        Ok(Json::Number(number))
+    }
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.position += 1;
+        let mut output = String::new();
+        loop {
+            let start = self.position;
+            match self.bump() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') => return Ok(output),
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape(start)?,
+                        _ => {
+                            self.position = start;
+                            return Err(self.error("Invalid escape"));
+                        }
+                    };
+                    output.push(escaped);
+                }
+                Some(x) if (x as u32) < 0x20 => {
+                    self.position = start;
+                    return Err(self.error("Control character in a string"));
+                }
+                Some(x) => output.push(x),
+            }
+        }
+    }
+    ///The character of a `\u` escape,joining surrogate pairs.`start` is where the escape began.
+    fn unicode_escape(&mut self, start: usize) -> Result<char, ParseError> {
+        let high = self.hex4(start)?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            if !self.text[self.position..].starts_with("\\u") {
+                self.position = start;
+                return Err(self.error("Unpaired surrogate"));
+            }
+            self.position += 2;
+            let low = self.hex4(start)?;
+            if !(0xdc00..0xe000).contains(&low) {
+                self.position = start;
+                return Err(self.error("Unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| {
+            self.position = start;
+            self.error("Unpaired surrogate")
+        })
+    }
+    fn hex4(&mut self, start: usize) -> Result<u32, ParseError> {
+        let digits = self.text.get(self.position..self.position + 4);
+        match digits.and_then(|x| u32::from_str_radix(x, 16).ok()) {
+            Some(x) if digits.is_some_and(|x| x.bytes().all(|x| x.is_ascii_hexdigit())) => {
+                self.position += 4;
+                Ok(x)
+            }
+            _ => {
+                self.position = start;
+                Err(self.error("Invalid unicode escape"))
+            }
+        }
+    }
+}
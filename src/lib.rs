@@ -14,3 +14,25 @@ pub mod lox_object;
 pub mod lox_error;
 ///This is to run lox from a file.It 
 pub mod lox_runner;
+///Symbol handles for identifiers.
+pub mod interner;
+///Reading and writing JSON,for the built-ins and the tools that talk JSON.
+pub mod json;
+///Re-printing lox code in the one style of `rlox fmt`.
+pub mod formatter;
+///Warnings about lox code that runs but is likely a bug,for `rlox lint`.
+pub mod lint;
+///The language server behind the `rlox-lsp` binary.
+pub mod lsp;
+///The debug adapter behind the `rlox-dap` binary.
+pub mod dap;
+///Call counts and timings of a run,for `rlox --profile`.
+pub mod profiler;
+///What the tests ran of the code they test,for `rlox test --coverage`.
+pub mod coverage;
+///A log of every statement,call and variable set of a run,for `rlox --trace`.
+pub mod trace;
+///Colouring lox code for terminals and web pages,for `rlox highlight`.
+pub mod highlight;
+///API documentation from the `///` comments of functions,for `rlox doc`.
+pub mod doc;
@@ -0,0 +1,511 @@
+//!`rlox lint`,warnings about code that runs but is likely a bug.
+//!
+//!Every warning has a code.A comment `// lint: allow L001` (or the name,`unused-variable`)
+//!silences it on the line of the comment and on the line after,`// lint: allow-file L001`
+//!silences it in the whole file.Several codes can be given,split by spaces or commas.
+use std::{collections::HashMap, error::Error, fmt::Display, rc::Rc};
+
+use ascii::AsAsciiStr;
+
+use crate::{
+    ast::{
+        expression::{Expr, ExprView},
+        statement::{DynStmt, Stmt, StmtView},
+    },
+    lox_object::{builtinfunction, LoxCallable},
+    parser::Parser,
+    token::{Scanner, Token, TokenType},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Code {
+    ///A local variable or a parameter that is never read.
+    UnusedVariable,
+    ///A `var` hiding a variable or a parameter of an enclosing scope.
+    Shadowing,
+    ///A statement after a `return` of the same block.
+    Unreachable,
+    ///`x = x;`
+    SelfAssignment,
+    ///Comparing values whose types are known to differ.
+    MixedComparison,
+    ///A built-in called with a number of arguments it doesn't take.
+    BuiltinArity,
+}
+impl Code {
+    pub const ALL: [Code; 6] = [
+        Code::UnusedVariable,
+        Code::Shadowing,
+        Code::Unreachable,
+        Code::SelfAssignment,
+        Code::MixedComparison,
+        Code::BuiltinArity,
+    ];
+    pub fn code(&self) -> &'static str {
+        match self {
+            Code::UnusedVariable => "L001",
+            Code::Shadowing => "L002",
+            Code::Unreachable => "L003",
+            Code::SelfAssignment => "L004",
+            Code::MixedComparison => "L005",
+            Code::BuiltinArity => "L006",
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Code::UnusedVariable => "unused-variable",
+            Code::Shadowing => "shadowing",
+            Code::Unreachable => "unreachable-code",
+            Code::SelfAssignment => "self-assignment",
+            Code::MixedComparison => "mixed-comparison",
+            Code::BuiltinArity => "builtin-arity",
+        }
+    }
+    ///The code named by its code or its name.
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|x| x.code() == text || x.name() == text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub code: Code,
+    ///Line of the code,starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}:warning[{}] {}:{}",
+            self.line,
+            self.code.code(),
+            self.code.name(),
+            self.message
+        )
+    }
+}
+
+///The warnings of `code` in the order of their lines,or the scanner and parser errors if it
+///isn't a valid program.
+pub fn lint<'a>(code: &'a str) -> Result<Vec<Warning>, Box<dyn Error + 'a>> {
+    let tokens = Scanner::new(code.as_ascii_str()?).scan_tokens()?;
+    let statements = Parser::new(&tokens).parse()?;
+    let builtins = builtinfunction::functions()
+        .into_iter()
+        .map(|x| (x.name().to_string(), x))
+        .collect();
+    let mut linter = Linter {
+        source: code,
+        builtins,
+        globals: Vec::new(),
+        scopes: vec![HashMap::new()],
+        locals: Vec::new(),
+        warnings: Vec::new(),
+    };
+    for x in statements.iter() {
+        linter.collect_globals(x.as_ref());
+    }
+    linter.statements(&statements);
+    let allowed = Allowed::new(&tokens);
+    let mut warnings = linter.warnings;
+    warnings.retain(|x| !allowed.contains(x));
+    warnings.sort_by_key(|x| x.line);
+    Ok(warnings)
+}
+
+///The warnings silenced by `lint:` comments.
+#[derive(Debug, Default)]
+struct Allowed {
+    file: Vec<Code>,
+    lines: Vec<(usize, Code)>,
+}
+impl Allowed {
+    fn new(tokens: &[Token]) -> Self {
+        let mut output = Self::default();
+        for token in tokens {
+            let trivia = token.trivia();
+            for (offset, _) in trivia.match_indices("//") {
+                //The token is on its line,every line break after the comment moves up one.
+                let line = token.line() - trivia[offset..].matches('\n').count();
+                let end = trivia[offset..]
+                    .find('\n')
+                    .map_or(trivia.len(), |x| offset + x);
+                let comment = trivia[offset + 2..end].trim();
+                let Some(rest) = comment.strip_prefix("lint:") else {
+                    continue;
+                };
+                let rest = rest.trim_start();
+                let (file, codes) = match rest.strip_prefix("allow-file") {
+                    Some(x) => (true, x),
+                    None => match rest.strip_prefix("allow") {
+                        Some(x) => (false, x),
+                        None => continue,
+                    },
+                };
+                let codes = codes.split([' ', ',']).filter_map(Code::parse);
+                for code in codes {
+                    if file {
+                        output.file.push(code);
+                    } else {
+                        output.lines.push((line, code));
+                        output.lines.push((line + 1, code));
+                    }
+                }
+            }
+        }
+        output
+    }
+    fn contains(&self, warning: &Warning) -> bool {
+        self.file.contains(&warning.code) || self.lines.contains(&(warning.line, warning.code))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LocalKind {
+    Variable,
+    Parameter,
+    Function,
+}
+#[derive(Debug)]
+struct Local {
+    name: String,
+    line: usize,
+    kind: LocalKind,
+    read: bool,
+}
+
+struct Linter<'s> {
+    source: &'s str,
+    builtins: HashMap<String, Rc<dyn LoxCallable<'static>>>,
+    ///Names declared at the top level anywhere in the file,they hide the built-ins.
+    globals: Vec<String>,
+    ///Names visible at this point with their index in `locals`,innermost scope last.
+    scopes: Vec<HashMap<String, usize>>,
+    locals: Vec<Local>,
+    warnings: Vec<Warning>,
+}
+impl<'a> Linter<'_> {
+    fn warn(&mut self, code: Code, line: usize, message: String) {
+        self.warnings.push(Warning {
+            code,
+            line,
+            message,
+        });
+    }
+    fn collect_globals(&mut self, statement: &(dyn Stmt<'a> + 'a)) {
+        match statement.view() {
+            StmtView::Var { name, .. } | StmtView::Function { name, .. } => {
+                self.globals.push(name.as_str().to_string())
+            }
+            StmtView::Statements(x) => {
+                for x in x {
+                    self.collect_globals(x.as_ref());
+                }
+            }
+            _ => (),
+        }
+    }
+    fn declare(&mut self, name: Token<'a>, kind: LocalKind) {
+        let global = self.scopes.len() == 1;
+        if kind == LocalKind::Variable && !global {
+            let outer = self.scopes[..self.scopes.len() - 1]
+                .iter()
+                .rev()
+                .find_map(|x| x.get(name.as_str()))
+                .map(|x| &self.locals[*x]);
+            if let Some(outer) = outer.filter(|x| x.kind != LocalKind::Function) {
+                let message = format!(
+                    "{} shadows the one declared at line {}.",
+                    name.as_str(),
+                    outer.line
+                );
+                self.warn(Code::Shadowing, name.line(), message);
+            }
+        }
+        let index = self.locals.len();
+        self.locals.push(Local {
+            name: name.as_str().to_string(),
+            line: name.line(),
+            kind,
+            //Globals can be read by code this pass doesn't see,like later runs of a REPL.
+            read: global,
+        });
+        let scope = self.scopes.last_mut().unwrap();
+        scope.insert(name.as_str().to_string(), index);
+    }
+    fn resolve(&self, name: &str) -> Option<usize> {
+        self.scopes.iter().rev().find_map(|x| x.get(name).copied())
+    }
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+    fn end_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap();
+        let mut unused: Vec<_> = scope
+            .into_values()
+            .filter(|x| !self.locals[*x].read && !self.locals[*x].name.starts_with('_'))
+            .collect();
+        unused.sort();
+        for x in unused {
+            let local = &self.locals[x];
+            let what = match local.kind {
+                LocalKind::Variable => "variable",
+                LocalKind::Parameter => "parameter",
+                LocalKind::Function => "function",
+            };
+            let message = format!("the {} {} is never read.", what, local.name);
+            self.warn(Code::UnusedVariable, local.line, message);
+        }
+    }
+    fn statements(&mut self, statements: &[DynStmt<'a>]) {
+        let mut returned = None;
+        for x in statements {
+            let start = self.start(x.as_ref());
+            if let Some((return_start, start)) = returned.zip(start) {
+                //The increment of a `for` loop comes after its body in the tree,but not in the
+                //source.
+                if start.0 > return_start {
+                    let message = "the code after a return never runs.".to_string();
+                    self.warn(Code::Unreachable, start.1, message);
+                    returned = None;
+                }
+            }
+            if let StmtView::Return(_) = x.view() {
+                returned = start.map(|x| x.0);
+            }
+            self.statement(x.as_ref());
+        }
+    }
+    fn statement(&mut self, statement: &(dyn Stmt<'a> + 'a)) {
+        match statement.view() {
+            StmtView::Expression(x) | StmtView::Return(x) => self.expression(x),
+            StmtView::Var { name, initializer } => {
+                self.expression(initializer);
+                self.declare(name, LocalKind::Variable);
+            }
+            StmtView::Block(x) => {
+                self.begin_scope();
+                self.statements(x);
+                self.end_scope();
+            }
+            StmtView::Statements(x) => self.statements(x),
+            StmtView::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.expression(condition);
+                self.statement(then_branch);
+                if let Some(x) = else_branch {
+                    self.statement(x);
+                }
+            }
+            StmtView::While {
+                condition, body, ..
+            } => {
+                self.expression(condition);
+                self.statement(body);
+            }
+            StmtView::Function {
+                name,
+                parameters,
+                body,
+                ..
+            } => {
+                self.declare(name, LocalKind::Function);
+                self.begin_scope();
+                for x in parameters {
+                    self.declare(*x, LocalKind::Parameter);
+                }
+                self.statement(body);
+                self.end_scope();
+            }
+        }
+    }
+    fn expression(&mut self, expression: &(dyn Expr<'a> + 'a)) {
+        match expression.view() {
+            ExprView::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                self.comparison(left, operator, right);
+                self.expression(left);
+                self.expression(right);
+            }
+            ExprView::Logical { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            ExprView::Unary { right, .. } | ExprView::Grouping(right) => self.expression(right),
+            ExprView::Literal(_) | ExprView::Value(_) => (),
+            ExprView::Variable(name) => {
+                if let Some(x) = self.resolve(name.as_str()) {
+                    self.locals[x].read = true;
+                }
+            }
+            ExprView::Assign { name, value } => {
+                if let ExprView::Variable(x) = value.view() {
+                    if x.as_str() == name.as_str() {
+                        let message = format!("{} is assigned to itself.", name.as_str());
+                        self.warn(Code::SelfAssignment, name.line(), message);
+                    }
+                }
+                self.expression(value);
+            }
+            ExprView::Call {
+                callee,
+                paren,
+                arguments,
+            } => {
+                if let ExprView::Variable(name) = callee.view() {
+                    self.builtin_call(name, paren, arguments.len());
+                }
+                self.expression(callee);
+                for x in arguments {
+                    self.expression(x.as_ref());
+                }
+            }
+        }
+    }
+    fn comparison(
+        &mut self,
+        left: &(dyn Expr<'a> + 'a),
+        operator: Token<'a>,
+        right: &(dyn Expr<'a> + 'a),
+    ) {
+        use TokenType as T;
+        let outcome = match operator.get_type() {
+            T::EqualEqual => "is always false",
+            T::BangEqual => "is always true",
+            T::Greater | T::GreaterEqual | T::Less | T::LessEqual => "is a runtime error",
+            _ => return,
+        };
+        let (Some(left), Some(right)) = (static_type(left), static_type(right)) else {
+            return;
+        };
+        if left != right {
+            let message = format!("comparing a {} with a {} {}.", left, right, outcome);
+            self.warn(Code::MixedComparison, operator.line(), message);
+        }
+    }
+    fn builtin_call(&mut self, name: Token<'a>, paren: Token<'a>, arguments: usize) {
+        let name = name.as_str();
+        if self.resolve(name).is_some() || self.globals.iter().any(|x| x == name) {
+            return;
+        }
+        let Some(builtin) = self.builtins.get(name) else {
+            return;
+        };
+        if !builtin.arity(arguments) {
+            let message = format!("{} can't be called with {} arguments.", name, arguments);
+            self.warn(Code::BuiltinArity, paren.line(), message);
+        }
+    }
+    ///Byte offset and line of the first token of `statement`,if it has one.
+    fn start(&self, statement: &(dyn Stmt<'a> + 'a)) -> Option<(usize, usize)> {
+        let mut tokens = Vec::new();
+        statement_tokens(statement, &mut tokens);
+        tokens
+            .into_iter()
+            .filter_map(|x| Some((x.range_in(self.source)?.start, x.line())))
+            .min()
+    }
+}
+
+///The type of the value `expression` always gives,if it can be known without running it.
+fn static_type(expression: &dyn Expr) -> Option<&'static str> {
+    use TokenType as T;
+    match expression.view() {
+        ExprView::Literal(x) => match x.get_type() {
+            T::Number => Some("number"),
+            T::String => Some("string"),
+            T::True | T::False => Some("boolean"),
+            T::Nil => Some("nil"),
+            _ => None,
+        },
+        ExprView::Value(x) => Some(x.type_name()),
+        ExprView::Grouping(x) => static_type(x),
+        ExprView::Unary { operator, .. } if operator.match_token(&T::Bang) => Some("boolean"),
+        ExprView::Unary { right, .. } => static_type(right).filter(|x| *x == "number"),
+        _ => None,
+    }
+}
+fn statement_tokens<'a>(statement: &(dyn Stmt<'a> + 'a), output: &mut Vec<Token<'a>>) {
+    match statement.view() {
+        StmtView::Expression(x) | StmtView::Return(x) => expression_tokens(x, output),
+        StmtView::Var { name, initializer } => {
+            output.push(name);
+            expression_tokens(initializer, output);
+        }
+        StmtView::Block(x) | StmtView::Statements(x) => {
+            for x in x {
+                statement_tokens(x.as_ref(), output);
+            }
+        }
+        StmtView::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            expression_tokens(condition, output);
+            statement_tokens(then_branch, output);
+            if let Some(x) = else_branch {
+                statement_tokens(x, output);
+            }
+        }
+        StmtView::While {
+            condition, body, ..
+        } => {
+            expression_tokens(condition, output);
+            statement_tokens(body, output);
+        }
+        StmtView::Function { name, .. } => output.push(name),
+    }
+}
+fn expression_tokens<'a>(expression: &(dyn Expr<'a> + 'a), output: &mut Vec<Token<'a>>) {
+    match expression.view() {
+        ExprView::Binary {
+            left,
+            operator,
+            right,
+        }
+        | ExprView::Logical {
+            left,
+            operator,
+            right,
+        } => {
+            output.push(operator);
+            expression_tokens(left, output);
+            expression_tokens(right, output);
+        }
+        ExprView::Unary { operator, right } => {
+            output.push(operator);
+            expression_tokens(right, output);
+        }
+        ExprView::Grouping(x) => expression_tokens(x, output),
+        ExprView::Literal(x) | ExprView::Variable(x) => output.push(x),
+        ExprView::Value(_) => (),
+        ExprView::Assign { name, value } => {
+            output.push(name);
+            expression_tokens(value, output);
+        }
+        ExprView::Call {
+            callee,
+            paren,
+            arguments,
+        } => {
+            output.push(paren);
+            expression_tokens(callee, output);
+            for x in arguments {
+                expression_tokens(x.as_ref(), output);
+            }
+        }
+    }
+}
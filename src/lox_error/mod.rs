@@ -9,6 +9,11 @@ impl<Err:Error> Default for Errors<Err>{
         Vec::new().into()
     }
 }   
+impl<Err:Error> Errors<Err>{
+    pub fn iter(&self)->impl Iterator<Item=&Err>{
+        self.0.iter()
+    }
+}
 impl<Err:Error> From<Vec<Err>> for Errors<Err>{
     fn from(value: Vec<Err>) -> Self {
         Errors(value.into_boxed_slice())
@@ -17,7 +22,7 @@ impl<Err:Error> From<Vec<Err>> for Errors<Err>{
 impl<Err:Error> Display for Errors<Err>{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for x in self.0.iter(){
-            write!(f,"{}\n",x)?
+            writeln!(f,"{}",x)?
         }
         Ok(())
     }
@@ -0,0 +1,41 @@
+//!Built-ins to look at the type of a value and convert between types.
+use super::{string_arg, NativeFunc};
+use crate::lox_object::Values;
+
+///Every conversion built-in,ready to be defined in the global scope.
+pub fn functions<'a>() -> Vec<NativeFunc<'a>> {
+    vec![
+        NativeFunc::new("type", 1..=1, |_, args| {
+            Ok(Values::Str(args[0].type_name().into()))
+        }),
+        NativeFunc::new("str", 1..=1, |_, args| {
+            Ok(Values::Str(args[0].to_string().into()))
+        }),
+        NativeFunc::new("repr", 1..=1, |_, args| {
+            Ok(Values::Str(args[0].repr().into()))
+        }),
+        NativeFunc::new("bool", 1..=1, |_, args| Ok(args[0].is_truthy().into())),
+        NativeFunc::new("num", 1..=1, |_, args| {
+            let text = string_arg("num", args, 0)?;
+            match parse_number(&text) {
+                Some(x) => Ok(Values::Number(x)),
+                None => Err(format!("num can't read {:?} as a number.", text).into()),
+            }
+        }),
+    ]
+}
+
+///Read a number written like a lox literal,with an optional sign and surrounding spaces.
+fn parse_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (digits, None),
+    };
+    let is_digits = |x: &str| !x.is_empty() && x.bytes().all(|x| x.is_ascii_digit());
+    if !is_digits(whole) || !fraction.is_none_or(is_digits) {
+        return None;
+    }
+    text.parse().ok()
+}
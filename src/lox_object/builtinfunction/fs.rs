@@ -0,0 +1,81 @@
+//!Filesystem built-ins.
+//!
+//!Every one of them first asks the environment if the host allows filesystem access,and an
+//!error from the OS is turned into a runtime error that carries its message.
+use std::{error::Error, fs, io, io::Write};
+
+use super::{string_arg, NativeFunc};
+use crate::{interpreter::environment::Environment, lox_object::Values};
+
+///Every filesystem built-in,ready to be defined in the global scope.
+pub fn functions<'a>() -> Vec<NativeFunc<'a>> {
+    vec![
+        NativeFunc::new("read_file", 1..=1, |env, args| {
+            let path = path_arg("read_file", env, args)?;
+            let text = fs::read_to_string(&*path).map_err(|x| os_error("read_file", &path, x))?;
+            Ok(Values::Str(text.into()))
+        }),
+        NativeFunc::new("read_lines", 1..=1, |env, args| {
+            let path = path_arg("read_lines", env, args)?;
+            let text = fs::read_to_string(&*path).map_err(|x| os_error("read_lines", &path, x))?;
+            Ok(Values::List(
+                text.lines().map(|x| Values::Str(x.into())).collect(),
+            ))
+        }),
+        NativeFunc::new("write_file", 2..=2, |env, args| {
+            let path = path_arg("write_file", env, args)?;
+            let text = string_arg("write_file", args, 1)?;
+            fs::write(&*path, text.as_bytes()).map_err(|x| os_error("write_file", &path, x))?;
+            Ok(Values::Null)
+        }),
+        NativeFunc::new("append_file", 2..=2, |env, args| {
+            let path = path_arg("append_file", env, args)?;
+            let text = string_arg("append_file", args, 1)?;
+            fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(&*path)
+                .and_then(|mut x| x.write_all(text.as_bytes()))
+                .map_err(|x| os_error("append_file", &path, x))?;
+            Ok(Values::Null)
+        }),
+        NativeFunc::new("file_exists", 1..=1, |env, args| {
+            let path = path_arg("file_exists", env, args)?;
+            let exists = fs::exists(&*path).map_err(|x| os_error("file_exists", &path, x))?;
+            Ok(exists.into())
+        }),
+        NativeFunc::new("list_dir", 1..=1, |env, args| {
+            let path = path_arg("list_dir", env, args)?;
+            let mut names = Vec::new();
+            for entry in fs::read_dir(&*path).map_err(|x| os_error("list_dir", &path, x))? {
+                let entry = entry.map_err(|x| os_error("list_dir", &path, x))?;
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+            //The OS gives them in any order,sorting keeps scripts deterministic.
+            names.sort();
+            Ok(Values::List(
+                names.into_iter().map(|x| Values::Str(x.into())).collect(),
+            ))
+        }),
+        NativeFunc::new("remove_file", 1..=1, |env, args| {
+            let path = path_arg("remove_file", env, args)?;
+            fs::remove_file(&*path).map_err(|x| os_error("remove_file", &path, x))?;
+            Ok(Values::Null)
+        }),
+    ]
+}
+
+///The path in the first argument,after checking the host allows filesystem access.
+fn path_arg(
+    name: &str,
+    env: &Environment,
+    args: &[Values],
+) -> Result<std::rc::Rc<str>, Box<dyn Error>> {
+    if !env.filesystem_allowed() {
+        return Err(format!("{} can't be used,filesystem access is disabled.", name).into());
+    }
+    string_arg(name, args, 0)
+}
+fn os_error(name: &str, path: &str, error: io::Error) -> Box<dyn Error> {
+    format!("{} failed on {:?}:{}.", name, path, error).into()
+}
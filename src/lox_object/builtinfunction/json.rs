@@ -0,0 +1,94 @@
+//!`json_encode` and `json_decode`,converting between `Values` and `crate::json`.
+//!
+//!Arrays are lists.Lox has no value for a JSON object,so decoding one is an error that says
+//!where it is,and functions can't be encoded.
+use std::error::Error;
+
+use super::{string_arg, NativeFunc};
+use crate::{
+    json::{self, Json, ParseError},
+    lox_object::Values,
+};
+
+///Both JSON built-ins,ready to be defined in the global scope.
+pub fn functions<'a>() -> Vec<NativeFunc<'a>> {
+    vec![
+        NativeFunc::new("json_encode", 1..=2, |_, args| {
+            let json = to_json(&args[0])?;
+            let pretty = args.get(1).is_some_and(Values::is_truthy);
+            let text = if pretty {
+                json.to_pretty_string()
+            } else {
+                json.to_string()
+            };
+            Ok(Values::Str(text.into()))
+        }),
+        NativeFunc::new("json_decode", 1..=1, |_, args| {
+            let text = string_arg("json_decode", args, 0)?;
+            let json = json::parse(&text).map_err(|x| decode_error(&x))?;
+            from_json(json, &text)
+        }),
+    ]
+}
+
+fn to_json(value: &Values) -> Result<Json, Box<dyn Error>> {
+    Ok(match value {
+        Values::Null => Json::Null,
+        Values::Boolean(_) => Json::Bool(value.is_truthy()),
+        Values::Number(x) if x.is_finite() => Json::Number(*x),
+        Values::Number(x) => return Err(format!("json_encode can't encode {}.", x).into()),
+        Values::Str(x) => Json::Str(x.to_string()),
+        Values::List(x) => Json::Array(x.iter().map(to_json).collect::<Result<_, _>>()?),
+        Values::Fn(_) => return Err("json_encode can't encode a function.".into()),
+    })
+}
+fn from_json<'a>(json: Json, text: &str) -> Result<Values<'a>, Box<dyn Error>> {
+    Ok(match json {
+        Json::Null => Values::Null,
+        Json::Bool(x) => x.into(),
+        Json::Number(x) => Values::Number(x),
+        Json::Str(x) => Values::Str(x.into()),
+        Json::Array(x) => Values::List(
+            x.into_iter()
+                .map(|x| from_json(x, text))
+                .collect::<Result<_, _>>()?,
+        ),
+        Json::Object(_) => {
+            //The parser doesn't keep positions,the first object in the text is the one found.
+            let position = first_object(text);
+            return Err(decode_error(&ParseError {
+                message: "Objects are not supported,lox has no map value".to_string(),
+                ..position
+            }));
+        }
+    })
+}
+///Position of the first `{` outside a string.
+fn first_object(text: &str) -> ParseError {
+    let (mut line, mut column) = (1, 1);
+    let mut in_string = false;
+    let mut escaped = false;
+    for x in text.chars() {
+        match x {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' if !in_string => break,
+            _ => (),
+        }
+        if x == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    ParseError {
+        line,
+        column,
+        message: String::new(),
+    }
+}
+fn decode_error(error: &ParseError) -> Box<dyn Error> {
+    format!("json_decode failed:{}", error).into()
+}
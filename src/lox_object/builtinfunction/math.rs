@@ -0,0 +1,81 @@
+use std::{
+    error::Error,
+    f64::consts::{E, PI},
+};
+
+use super::{number_arg, NativeFunc};
+use crate::lox_object::Values;
+
+pub const CONSTANTS: [(&str, f64); 2] = [("PI", PI), ("E", E)];
+
+///Every math built-in,ready to be defined in the global scope.
+pub fn functions<'a>() -> Vec<NativeFunc<'a>> {
+    vec![
+        NativeFunc::new("sqrt", 1..=1, |_, args| unary("sqrt", args, f64::sqrt)),
+        NativeFunc::new("abs", 1..=1, |_, args| unary("abs", args, f64::abs)),
+        NativeFunc::new("floor", 1..=1, |_, args| unary("floor", args, f64::floor)),
+        NativeFunc::new("ceil", 1..=1, |_, args| unary("ceil", args, f64::ceil)),
+        NativeFunc::new("round", 1..=1, |_, args| unary("round", args, f64::round)),
+        NativeFunc::new("sin", 1..=1, |_, args| unary("sin", args, f64::sin)),
+        NativeFunc::new("cos", 1..=1, |_, args| unary("cos", args, f64::cos)),
+        NativeFunc::new("tan", 1..=1, |_, args| unary("tan", args, f64::tan)),
+        NativeFunc::new("log", 1..=1, |_, args| unary("log", args, f64::ln)),
+        NativeFunc::new("exp", 1..=1, |_, args| unary("exp", args, f64::exp)),
+        NativeFunc::new("pow", 2..=2, |_, args| binary("pow", args, f64::powf)),
+        NativeFunc::new("atan2", 2..=2, |_, args| binary("atan2", args, f64::atan2)),
+        NativeFunc::new("min", 1..=usize::MAX, |_, args| fold("min", args, f64::min)),
+        NativeFunc::new("max", 1..=usize::MAX, |_, args| fold("max", args, f64::max)),
+        NativeFunc::new("is_nan", 1..=1, |_, args| {
+            Ok(number_arg("is_nan", args, 0)?.is_nan().into())
+        }),
+        NativeFunc::new("is_finite", 1..=1, |_, args| {
+            Ok(number_arg("is_finite", args, 0)?.is_finite().into())
+        }),
+        NativeFunc::new("idiv", 2..=2, |_, args| {
+            let (x, y) = divisor("idiv", args)?;
+            Ok(Values::Number((x / y).floor()))
+        }),
+        NativeFunc::new("mod", 2..=2, |_, args| {
+            let (x, y) = divisor("mod", args)?;
+            //Floored,so the result has the sign of the divisor.
+            Ok(Values::Number(x - y * (x / y).floor()))
+        }),
+    ]
+}
+
+fn unary<'a>(
+    name: &str,
+    args: &[Values],
+    func: fn(f64) -> f64,
+) -> Result<Values<'a>, Box<dyn Error>> {
+    Ok(Values::Number(func(number_arg(name, args, 0)?)))
+}
+fn binary<'a>(
+    name: &str,
+    args: &[Values],
+    func: fn(f64, f64) -> f64,
+) -> Result<Values<'a>, Box<dyn Error>> {
+    let x = number_arg(name, args, 0)?;
+    let y = number_arg(name, args, 1)?;
+    Ok(Values::Number(func(x, y)))
+}
+fn fold<'a>(
+    name: &str,
+    args: &[Values],
+    func: fn(f64, f64) -> f64,
+) -> Result<Values<'a>, Box<dyn Error>> {
+    let mut ans = number_arg(name, args, 0)?;
+    for index in 1..args.len() {
+        ans = func(ans, number_arg(name, args, index)?);
+    }
+    Ok(Values::Number(ans))
+}
+///The two arguments of `idiv` and `mod`,refusing a zero divisor.
+fn divisor(name: &str, args: &[Values]) -> Result<(f64, f64), Box<dyn Error>> {
+    let x = number_arg(name, args, 0)?;
+    let y = number_arg(name, args, 1)?;
+    if y == 0.0 {
+        return Err(format!("{} got division by zero.", name).into());
+    }
+    Ok((x, y))
+}
@@ -1,6 +1,123 @@
-use std::time::Instant;
+use std::{error::Error, fmt::Debug, ops::RangeInclusive, rc::Rc};
 
-use super::LoxCallable;
+use super::{LoxCallable, Values};
+use crate::interpreter::environment::Environment;
+pub mod convert;
+pub mod fs;
+pub mod json;
+pub mod math;
+pub mod process;
+pub mod random;
+pub mod string;
+pub mod time;
+
+type NativeResult<'a> = Result<Values<'a>, Box<dyn Error>>;
+///Every built-in function,the globals a new interpreter starts with.
+pub fn functions<'a>() -> Vec<Rc<dyn LoxCallable<'a> + 'a>> {
+    let mut output: Vec<Rc<dyn LoxCallable<'a> + 'a>> = vec![
+        Rc::new(PrintFunc),
+        Rc::new(ClockFunc::new()),
+        Rc::new(GcFunc),
+        Rc::new(AssertFunc),
+    ];
+    let natives = math::functions()
+        .into_iter()
+        .chain(string::functions())
+        .chain(convert::functions())
+        .chain(fs::functions())
+        .chain(json::functions())
+        .chain(process::functions())
+        .chain(random::functions())
+        .chain(time::functions());
+    for x in natives {
+        output.push(Rc::new(x));
+    }
+    output
+}
+///A built-in function written in rust.
+///The arguments are already checked against `arity` when `func` runs.
+pub struct NativeFunc<'a> {
+    name: &'static str,
+    arity: RangeInclusive<usize>,
+    func: fn(&mut Environment<'a>, &[Values<'a>]) -> NativeResult<'a>,
+}
+impl<'a> NativeFunc<'a> {
+    pub fn new(
+        name: &'static str,
+        arity: RangeInclusive<usize>,
+        func: fn(&mut Environment<'a>, &[Values<'a>]) -> NativeResult<'a>,
+    ) -> Self {
+        Self { name, arity, func }
+    }
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+impl Debug for NativeFunc<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NativeFunc({})", self.name)
+    }
+}
+impl<'a> LoxCallable<'a> for NativeFunc<'a> {
+    fn call(&self, env: &mut Environment<'a>, args: &[Values<'a>]) -> NativeResult<'a> {
+        let ans = (self.func)(env, args)?;
+        env.heap_mut().allocate(ans.heap_size())?;
+        Ok(ans)
+    }
+    fn arity(&self, args_num: usize) -> bool {
+        self.arity.contains(&args_num)
+    }
+    fn name(&self) -> &str {
+        self.name
+    }
+}
+///Get argument `index` as a number,or an error naming the function `name`.
+pub fn number_arg(name: &str, args: &[Values], index: usize) -> Result<f64, Box<dyn Error>> {
+    match &args[index] {
+        Values::Number(x) => Ok(*x),
+        x => Err(type_error(name, "number", index, x)),
+    }
+}
+///Get argument `index` as a whole number that is not negative,for counts and positions.
+pub fn index_arg(name: &str, args: &[Values], index: usize) -> Result<usize, Box<dyn Error>> {
+    let x = number_arg(name, args, index)?;
+    if x < 0.0 || x.fract() != 0.0 {
+        return Err(format!(
+            "{} expects a whole number that is not negative as argument {},got {}.",
+            name,
+            index + 1,
+            x
+        )
+        .into());
+    }
+    Ok(x as usize)
+}
+pub fn string_arg(name: &str, args: &[Values], index: usize) -> Result<Rc<str>, Box<dyn Error>> {
+    match &args[index] {
+        Values::Str(x) => Ok(x.clone()),
+        x => Err(type_error(name, "string", index, x)),
+    }
+}
+pub fn list_arg<'a>(
+    name: &str,
+    args: &[Values<'a>],
+    index: usize,
+) -> Result<Rc<[Values<'a>]>, Box<dyn Error>> {
+    match &args[index] {
+        Values::List(x) => Ok(x.clone()),
+        x => Err(type_error(name, "list", index, x)),
+    }
+}
+fn type_error(name: &str, expected: &str, index: usize, got: &Values) -> Box<dyn Error> {
+    format!(
+        "{} expects a {} as argument {},got {}.",
+        name,
+        expected,
+        index + 1,
+        got.type_name()
+    )
+    .into()
+}
 #[derive(Debug)]
 pub struct PrintFunc;
 impl<'a> LoxCallable<'a> for PrintFunc {
@@ -17,14 +134,19 @@ impl<'a> LoxCallable<'a> for PrintFunc {
         Ok(super::Values::Null)
     }
     fn arity(&self,_args_num:usize) -> bool {
-        return true;
+        true
+    }
+    fn name(&self) -> &str {
+        "print"
     }
 }
-#[derive(Debug)]
-pub struct ClockFunc(Instant);
+///Seconds read from the clock of the interpreter,so a host that replaces the clock controls it too.
+#[derive(Debug, Default)]
+pub struct ClockFunc;
+
 impl ClockFunc{
     pub fn new()->Self{
-        Self(Instant::now())
+        Self
     }
 }
 
@@ -34,10 +156,57 @@ impl<'a> LoxCallable<'a> for ClockFunc {
         env: &mut crate::interpreter::environment::Environment,
         _args: &[super::Values],
     ) -> Result<super::Values<'a>, Box<dyn std::error::Error>> {
-        let second=self.0.elapsed().as_secs_f64();
+        let second=env.clock().now();
         Ok(super::Values::Number(second))
     }
     fn arity(&self,args_num:usize) -> bool {
         args_num==0
     }
+    fn name(&self) -> &str {
+        "clock"
+    }
+}
+///Run the garbage collector and return the number of freed objects.
+#[derive(Debug)]
+pub struct GcFunc;
+impl<'a> LoxCallable<'a> for GcFunc {
+    fn call(
+        &self,
+        env: &mut crate::interpreter::environment::Environment,
+        _args: &[super::Values],
+    ) -> Result<super::Values<'a>, Box<dyn std::error::Error>> {
+        let freed = env.heap_mut().collect();
+        Ok(super::Values::Number(freed as f64))
+    }
+    fn arity(&self, args_num: usize) -> bool {
+        args_num == 0
+    }
+    fn name(&self) -> &str {
+        "gc"
+    }
+}
+///Fail with a runtime error naming the line of the call when the condition is false.
+#[derive(Debug)]
+pub struct AssertFunc;
+impl<'a> LoxCallable<'a> for AssertFunc {
+    fn call(
+        &self,
+        env: &mut crate::interpreter::environment::Environment,
+        args: &[super::Values],
+    ) -> Result<super::Values<'a>, Box<dyn std::error::Error>> {
+        if args[0].is_truthy() {
+            return Ok(super::Values::Null);
+        }
+        let error = match args.get(1) {
+            Some(message) => format!("Assertion failed at line {}:{}", env.call_line(), message),
+            None => format!("Assertion failed at line {}.", env.call_line()),
+        };
+        Err(error.into())
+    }
+    fn arity(&self, args_num: usize) -> bool {
+        args_num == 1 || args_num == 2
+    }
+    fn name(&self) -> &str {
+        "assert"
+    }
 }
@@ -0,0 +1,34 @@
+use super::{number_arg, string_arg, NativeFunc};
+use crate::{interpreter::process::Exit, lox_object::Values};
+
+///Every process built-in,ready to be defined in the global scope.
+pub fn functions<'a>() -> Vec<NativeFunc<'a>> {
+    vec![
+        NativeFunc::new("args", 0..=0, |env, _| {
+            let args = env.process().args().iter();
+            Ok(Values::List(
+                args.map(|x| Values::Str(x.as_str().into())).collect(),
+            ))
+        }),
+        NativeFunc::new("getenv", 1..=1, |env, args| {
+            let name = string_arg("getenv", args, 0)?;
+            Ok(match env.process().var(&name) {
+                Some(x) => Values::Str(x.into()),
+                None => Values::Null,
+            })
+        }),
+        NativeFunc::new("exit", 0..=1, |env, args| {
+            let code = match args.first() {
+                Some(_) => number_arg("exit", args, 0)?,
+                None => 0.0,
+            };
+            if code.fract() != 0.0 || code < i32::MIN as f64 || code > i32::MAX as f64 {
+                return Err(
+                    format!("exit expects a whole number as the code,got {}.", code).into(),
+                );
+            }
+            env.flush()?;
+            Err(Box::new(Exit(code as i32)))
+        }),
+    ]
+}
@@ -0,0 +1,66 @@
+//!Pseudo-random built-ins.
+//!
+//!The generator lives in the environment,so every interpreter has its own sequence,and after
+//!`seed(n)` the sequence is the same on every platform.
+use std::error::Error;
+
+use super::{list_arg, number_arg, NativeFunc};
+use crate::lox_object::Values;
+
+///Every random built-in,ready to be defined in the global scope.
+pub fn functions<'a>() -> Vec<NativeFunc<'a>> {
+    vec![
+        NativeFunc::new("seed", 1..=1, |env, args| {
+            //The same seed as `Interpreter::set_seed` with that number,a negative one wraps.
+            let seed = whole_arg("seed", args, 0)?;
+            env.rng_mut().seed(seed as u64);
+            Ok(Values::Null)
+        }),
+        NativeFunc::new("random", 0..=0, |env, _| {
+            Ok(Values::Number(env.rng_mut().next_f64()))
+        }),
+        NativeFunc::new("random_int", 2..=2, |env, args| {
+            let low = whole_arg("random_int", args, 0)?;
+            let high = whole_arg("random_int", args, 1)?;
+            if low > high {
+                return Err(format!("random_int got {} above {}.", low, high).into());
+            }
+            let span = high.abs_diff(low) + 1;
+            let offset = env.rng_mut().below(span);
+            Ok(Values::Number(low.wrapping_add(offset as i64) as f64))
+        }),
+        NativeFunc::new("shuffle", 1..=1, |env, args| {
+            let mut list = list_arg("shuffle", args, 0)?.to_vec();
+            //Fisher-Yates,from the back.
+            for i in (1..list.len()).rev() {
+                let j = env.rng_mut().below(i as u64 + 1) as usize;
+                list.swap(i, j);
+            }
+            Ok(Values::List(list.into()))
+        }),
+        NativeFunc::new("choice", 1..=1, |env, args| {
+            let list = list_arg("choice", args, 0)?;
+            if list.is_empty() {
+                return Err("choice can't pick from an empty list.".into());
+            }
+            let index = env.rng_mut().below(list.len() as u64) as usize;
+            Ok(list[index].clone())
+        }),
+    ]
+}
+
+///Bounds of `random_int` and seeds,whole numbers small enough to be exact in a lox number.
+fn whole_arg(name: &str, args: &[Values], index: usize) -> Result<i64, Box<dyn Error>> {
+    const LIMIT: f64 = (1u64 << 53) as f64;
+    let x = number_arg(name, args, index)?;
+    if x.fract() != 0.0 || x.abs() > LIMIT {
+        return Err(format!(
+            "{} expects a whole number as argument {},got {}.",
+            name,
+            index + 1,
+            x
+        )
+        .into());
+    }
+    Ok(x as i64)
+}
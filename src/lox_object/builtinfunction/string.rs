@@ -0,0 +1,185 @@
+//!String built-ins.
+//!
+//!Positions and lengths count unicode scalar values(what rust calls a `char`),not bytes,so
+//!`len(chr(955))` is 1.Positions start at 0,and a range is `start` included to `end` excluded.
+//!A position outside the string is a runtime error instead of being clamped.
+use std::{error::Error, rc::Rc};
+
+use super::{index_arg, list_arg, string_arg, type_error, NativeFunc};
+use crate::lox_object::Values;
+
+///Every string built-in,ready to be defined in the global scope.
+pub fn functions<'a>() -> Vec<NativeFunc<'a>> {
+    vec![
+        NativeFunc::new("len", 1..=1, |_, args| {
+            let len = match &args[0] {
+                Values::Str(x) => x.chars().count(),
+                Values::List(x) => x.len(),
+                x => return Err(type_error("len", "string or list", 0, x)),
+            };
+            Ok(Values::Number(len as f64))
+        }),
+        NativeFunc::new("substr", 3..=3, |_, args| {
+            let text = string_arg("substr", args, 0)?;
+            let start = index_arg("substr", args, 1)?;
+            let end = index_arg("substr", args, 2)?;
+            if start > end {
+                return Err(format!("substr got start {} after end {}.", start, end).into());
+            }
+            let start = byte_offset("substr", &text, start)?;
+            let end = byte_offset("substr", &text, end)?;
+            Ok(Values::Str(text[start..end].into()))
+        }),
+        NativeFunc::new("char_at", 2..=2, |_, args| {
+            let text = string_arg("char_at", args, 0)?;
+            let index = index_arg("char_at", args, 1)?;
+            match text.chars().nth(index) {
+                Some(x) => Ok(Values::Str(x.to_string().into())),
+                None => Err(out_of_range("char_at", index, &text)),
+            }
+        }),
+        NativeFunc::new("index_of", 2..=2, |_, args| {
+            let text = string_arg("index_of", args, 0)?;
+            let pattern = string_arg("index_of", args, 1)?;
+            let index = match text.find(&*pattern) {
+                Some(x) => text[..x].chars().count() as f64,
+                None => -1.0,
+            };
+            Ok(Values::Number(index))
+        }),
+        NativeFunc::new("contains", 2..=2, |_, args| {
+            let (text, pattern) = two_strings("contains", args)?;
+            Ok(text.contains(&*pattern).into())
+        }),
+        NativeFunc::new("starts_with", 2..=2, |_, args| {
+            let (text, pattern) = two_strings("starts_with", args)?;
+            Ok(text.starts_with(&*pattern).into())
+        }),
+        NativeFunc::new("ends_with", 2..=2, |_, args| {
+            let (text, pattern) = two_strings("ends_with", args)?;
+            Ok(text.ends_with(&*pattern).into())
+        }),
+        NativeFunc::new("upper", 1..=1, |_, args| {
+            Ok(Values::Str(
+                string_arg("upper", args, 0)?.to_uppercase().into(),
+            ))
+        }),
+        NativeFunc::new("lower", 1..=1, |_, args| {
+            Ok(Values::Str(
+                string_arg("lower", args, 0)?.to_lowercase().into(),
+            ))
+        }),
+        NativeFunc::new("trim", 1..=1, |_, args| {
+            Ok(Values::Str(string_arg("trim", args, 0)?.trim().into()))
+        }),
+        NativeFunc::new("replace", 3..=3, |_, args| {
+            let (text, from) = two_strings("replace", args)?;
+            let to = string_arg("replace", args, 2)?;
+            if from.is_empty() {
+                return Err("replace can't replace an empty string.".into());
+            }
+            Ok(Values::Str(text.replace(&*from, &to).into()))
+        }),
+        NativeFunc::new("split", 2..=2, |_, args| {
+            let (text, separator) = two_strings("split", args)?;
+            //An empty separator splits the string in characters.
+            let parts: Rc<[Values]> = if separator.is_empty() {
+                text.chars()
+                    .map(|x| Values::Str(x.to_string().into()))
+                    .collect()
+            } else {
+                text.split(&*separator)
+                    .map(|x| Values::Str(x.into()))
+                    .collect()
+            };
+            Ok(Values::List(parts))
+        }),
+        NativeFunc::new("join", 2..=2, |_, args| {
+            let list = list_arg("join", args, 0)?;
+            let separator = string_arg("join", args, 1)?;
+            let parts: Vec<String> = list.iter().map(|x| x.to_string()).collect();
+            Ok(Values::Str(parts.join(&separator).into()))
+        }),
+        NativeFunc::new("repeat", 2..=2, |env, args| {
+            let text = string_arg("repeat", args, 0)?;
+            let count = index_arg("repeat", args, 1)?;
+            //Checked before building it,the string can be far bigger than the memory limit.
+            let length = text
+                .len()
+                .checked_mul(count)
+                .filter(|x| *x <= isize::MAX as usize);
+            let Some(length) = length else {
+                return Err(format!(
+                    "repeat can't repeat a string {} times,it is too long.",
+                    args[1]
+                )
+                .into());
+            };
+            if env.heap_mut().remaining().is_some_and(|x| length > x) {
+                return Err(format!(
+                    "repeat would make a string of {} bytes,over the memory limit.",
+                    length
+                )
+                .into());
+            }
+            //The string is counted against the limit once it is returned,like any built-in result.
+            let mut output = String::new();
+            if output.try_reserve_exact(length).is_err() {
+                return Err(format!("repeat can't allocate a string of {} bytes.", length).into());
+            }
+            (0..count).for_each(|_| output.push_str(&text));
+            Ok(Values::Str(output.into()))
+        }),
+        NativeFunc::new("ord", 1..=1, |_, args| {
+            let text = string_arg("ord", args, 0)?;
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(x), None) => Ok(Values::Number(x as u32 as f64)),
+                _ => Err("ord expects a string of exactly one character.".into()),
+            }
+        }),
+        NativeFunc::new("chr", 1..=1, |_, args| {
+            let code = index_arg("chr", args, 0)?;
+            match u32::try_from(code).ok().and_then(char::from_u32) {
+                Some(x) => Ok(Values::Str(x.to_string().into())),
+                None => Err(format!("chr got {},which is not a unicode character.", code).into()),
+            }
+        }),
+        NativeFunc::new("list", 0..=usize::MAX, |_, args| {
+            Ok(Values::List(args.into()))
+        }),
+        NativeFunc::new("get", 2..=2, |_, args| {
+            let list = list_arg("get", args, 0)?;
+            let index = index_arg("get", args, 1)?;
+            match list.get(index) {
+                Some(x) => Ok(x.clone()),
+                None => Err(format!(
+                    "get index {} is out of range for a list of length {}.",
+                    index,
+                    list.len()
+                )
+                .into()),
+            }
+        }),
+    ]
+}
+
+fn two_strings(name: &str, args: &[Values]) -> Result<(Rc<str>, Rc<str>), Box<dyn Error>> {
+    Ok((string_arg(name, args, 0)?, string_arg(name, args, 1)?))
+}
+///Byte offset of the character at `index`,the length of the string is a valid position too.
+fn byte_offset(name: &str, text: &str, index: usize) -> Result<usize, Box<dyn Error>> {
+    let mut offsets = text.char_indices().map(|(x, _)| x).chain([text.len()]);
+    offsets
+        .nth(index)
+        .ok_or_else(|| out_of_range(name, index, text))
+}
+fn out_of_range(name: &str, index: usize, text: &str) -> Box<dyn Error> {
+    format!(
+        "{} index {} is out of range for a string of length {}.",
+        name,
+        index,
+        text.chars().count()
+    )
+    .into()
+}
@@ -0,0 +1,278 @@
+//!Date and time built-ins.
+//!
+//!Times are seconds since the Unix epoch,read from the clock of the environment so a host can
+//!replace it.Dates are always UTC,there is no time zone database.
+//!
+//!Patterns of `format_time` and `parse_time`:
+//!`%Y` year,`%m` month,`%d` day,`%H` hour,`%M` minute,`%S` second,`%j` day of the year,
+//!`%s` seconds since the epoch,`%%` a percent sign.`format_time` also knows `%y` two digit
+//!year,`%a`/`%A` weekday name and `%b`/`%B` month name.
+use std::{error::Error, time::Duration};
+
+use super::{number_arg, string_arg, NativeFunc};
+use crate::lox_object::Values;
+
+const SECONDS_PER_DAY: i64 = 86400;
+///Largest year `parse_time` reads either way,the day counts of further years overflow.
+const MAX_YEAR: i64 = 1_000_000_000;
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+///Every time built-in,ready to be defined in the global scope.
+pub fn functions<'a>() -> Vec<NativeFunc<'a>> {
+    vec![
+        NativeFunc::new("now", 0..=0, |env, _| Ok(Values::Number(env.clock().now()))),
+        NativeFunc::new("sleep", 1..=1, |env, args| {
+            let seconds = number_arg("sleep", args, 0)?;
+            //Past about 584 billion years the seconds don't fit in a `Duration`.
+            if Duration::try_from_secs_f64(seconds).is_err() {
+                return Err(format!("sleep expects a number of seconds,got {}.", seconds).into());
+            }
+            env.sleep(seconds)?;
+            Ok(Values::Null)
+        }),
+        NativeFunc::new("format_time", 2..=2, |_, args| {
+            let time = number_arg("format_time", args, 0)?;
+            let pattern = string_arg("format_time", args, 1)?;
+            Ok(Values::Str(format_time(time, &pattern)?.into()))
+        }),
+        NativeFunc::new("parse_time", 2..=2, |_, args| {
+            let text = string_arg("parse_time", args, 0)?;
+            let pattern = string_arg("parse_time", args, 1)?;
+            Ok(Values::Number(parse_time(&text, &pattern)?))
+        }),
+    ]
+}
+
+///A moment split in calendar fields.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+impl DateTime {
+    fn from_timestamp(time: i64) -> Self {
+        let days = time.div_euclid(SECONDS_PER_DAY);
+        let seconds = time.rem_euclid(SECONDS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: seconds / 3600,
+            minute: seconds / 60 % 60,
+            second: seconds % 60,
+        }
+    }
+    fn timestamp(&self) -> i64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        days * SECONDS_PER_DAY
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+    ///Day of the year,starting at 1.
+    fn ordinal(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1) + 1
+    }
+    ///0 is Monday.
+    fn weekday(&self) -> usize {
+        //The epoch was a Thursday.
+        (days_from_civil(self.year, self.month, self.day) + 3).rem_euclid(7) as usize
+    }
+}
+
+///Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+///From Howard Hinnant's `chrono`-compatible date algorithms.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let next = if month == 12 {
+        days_from_civil(year + 1, 1, 1)
+    } else {
+        days_from_civil(year, month + 1, 1)
+    };
+    (next - days_from_civil(year, month, 1)) as u32
+}
+
+fn format_time(time: f64, pattern: &str) -> Result<String, Box<dyn Error>> {
+    //About 292 billion years either way,past that the seconds don't fit in an i64.
+    if !time.is_finite() || time.abs() > 9.2e18 {
+        return Err(format!("format_time can't format the time {}.", time).into());
+    }
+    let time = time.floor() as i64;
+    let date = DateTime::from_timestamp(time);
+    let mut output = String::new();
+    let mut chars = pattern.chars();
+    while let Some(x) = chars.next() {
+        if x != '%' {
+            output.push(x);
+            continue;
+        }
+        let field = match chars.next() {
+            Some('Y') => date.year.to_string(),
+            Some('y') => format!("{:02}", date.year.rem_euclid(100)),
+            Some('m') => format!("{:02}", date.month),
+            Some('d') => format!("{:02}", date.day),
+            Some('H') => format!("{:02}", date.hour),
+            Some('M') => format!("{:02}", date.minute),
+            Some('S') => format!("{:02}", date.second),
+            Some('j') => format!("{:03}", date.ordinal()),
+            Some('s') => time.to_string(),
+            Some('a') => WEEKDAYS[date.weekday()][..3].to_string(),
+            Some('A') => WEEKDAYS[date.weekday()].to_string(),
+            Some('b') => MONTHS[date.month as usize - 1][..3].to_string(),
+            Some('B') => MONTHS[date.month as usize - 1].to_string(),
+            Some('%') => "%".to_string(),
+            x => return Err(unknown_pattern("format_time", x)),
+        };
+        output.push_str(&field);
+    }
+    Ok(output)
+}
+
+fn parse_time(text: &str, pattern: &str) -> Result<f64, Box<dyn Error>> {
+    let mismatch = || -> Box<dyn Error> {
+        format!(
+            "parse_time can't read {:?} with the pattern {:?}.",
+            text, pattern
+        )
+        .into()
+    };
+    let mut date = DateTime {
+        year: 1970,
+        month: 1,
+        day: 1,
+        hour: 0,
+        minute: 0,
+        second: 0,
+    };
+    let mut epoch = None;
+    let mut ordinal = None;
+    let mut rest = text;
+    let mut chars = pattern.chars();
+    while let Some(x) = chars.next() {
+        if x != '%' {
+            rest = rest.strip_prefix(x).ok_or_else(mismatch)?;
+            continue;
+        }
+        let field = chars.next();
+        if field == Some('%') {
+            rest = rest.strip_prefix('%').ok_or_else(mismatch)?;
+            continue;
+        }
+        let width = match field {
+            Some('Y' | 's') => None,
+            Some('m' | 'd' | 'H' | 'M' | 'S') => Some(2),
+            Some('j') => Some(3),
+            x => return Err(unknown_pattern("parse_time", x)),
+        };
+        let (number, left) = take_number(rest, width).ok_or_else(mismatch)?;
+        rest = left;
+        match field {
+            Some('Y') if number.abs() > MAX_YEAR => return Err(mismatch()),
+            Some('Y') => date.year = number,
+            Some('s') => epoch = Some(number),
+            Some('m') => date.month = number as u32,
+            Some('d') => date.day = number as u32,
+            Some('H') => date.hour = number as u32,
+            Some('M') => date.minute = number as u32,
+            //A leap second can't be told apart,it is read as the next minute.
+            Some('S') => date.second = number as u32,
+            _ => ordinal = Some(number),
+        }
+    }
+    if !rest.is_empty() {
+        return Err(mismatch());
+    }
+    if let Some(x) = epoch {
+        return Ok(x as f64);
+    }
+    if let Some(x) = ordinal {
+        let start = days_from_civil(date.year, 1, 1);
+        if !(1..=days_from_civil(date.year + 1, 1, 1) - start).contains(&x) {
+            return Err(mismatch());
+        }
+        (_, date.month, date.day) = civil_from_days(start + x - 1);
+    }
+    let valid = (1..=12).contains(&date.month)
+        && (1..=days_in_month(date.year, date.month)).contains(&date.day)
+        && date.hour < 24
+        && date.minute < 60
+        && date.second <= 60;
+    if !valid {
+        return Err(format!("parse_time read {:?},which is not a valid date.", text).into());
+    }
+    Ok(date.timestamp() as f64)
+}
+///Read a number with an optional sign at the start of `text`,`width` digits or as many as there
+///are.
+fn take_number(text: &str, width: Option<usize>) -> Option<(i64, &str)> {
+    let digits_start = if text.starts_with('-') { 1 } else { 0 };
+    let digits = text[digits_start..]
+        .bytes()
+        .take_while(|x| x.is_ascii_digit())
+        .count();
+    let digits = width.map_or(digits, |x| digits.min(x));
+    if digits == 0 || width.is_some() && digits_start == 1 {
+        return None;
+    }
+    let end = digits_start + digits;
+    Some((text[..end].parse().ok()?, &text[end..]))
+}
+fn unknown_pattern(name: &str, field: Option<char>) -> Box<dyn Error> {
+    match field {
+        Some(x) => format!("{} doesn't know the pattern %{}.", name, x).into(),
+        None => format!("{} got a pattern ending in %.", name).into(),
+    }
+}
@@ -1,14 +1,21 @@
 use std::{
     error::Error,
     fmt::{Debug, Display},
+    mem::size_of,
     rc::Rc,
     result::Result,
 };
 pub mod builtinfunction;
+#[cfg(feature = "nan-boxing")]
+pub mod nanbox;
 
 use crate::{
-    ast::statement::{self, DynStmt, FunctionDelc, RcStmt, Stmt},
-    interpreter::environment::{Environment, Scope},
+    ast::statement::RcStmt,
+    interner::Symbol,
+    interpreter::{
+        debug::Frame,
+        environment::{Environment, Scope},
+    },
     token::Token,
 };
 //#[derive(Debug,Clone)]
@@ -48,15 +55,25 @@ pub trait LoxCallable<'a>: Debug {
         args: &[Values<'a>],
     ) -> Result<Values<'a>, Box<dyn Error>>;
     fn arity(&self, input_number: usize) -> bool;
+    ///Name used in error messages.
+    fn name(&self) -> &str {
+        "function"
+    }
 }
 #[derive(Debug, Clone)]
 pub enum Values<'a> {
-    Str(String),
+    Str(Rc<str>),
     Boolean(f64),
     Number(f64),
     Fn(Rc<dyn LoxCallable<'a> + 'a>),
+    List(Rc<[Values<'a>]>),
     Null,
 }
+impl<'a> From<&Values<'a>> for Values<'a> {
+    fn from(value: &Values<'a>) -> Self {
+        value.clone()
+    }
+}
 impl From<bool> for Values<'_> {
     fn from(value: bool) -> Self {
         Self::to_boolean(value)
@@ -70,22 +87,32 @@ impl Display for Values<'_> {
             Boolean(0.0) => write!(f, "false"),
             Boolean(_) => write!(f, "true"),
             Number(x) => write!(f, "{x}"),
-            Null => write!(f, "Null"),
+            Null => write!(f, "nil"),
             Fn(_) => write!(f, "A function"),
+            List(x) => {
+                write!(f, "[")?;
+                for (i, x) in x.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    Display::fmt(x, f)?;
+                }
+                write!(f, "]")
+            }
         }
     }
 }
 #[derive(Debug)]
 pub struct LoxFunction<'a> {
     name: Token<'a>,
-    paren: Box<[Token<'a>]>,
+    paren: Rc<[Symbol]>,
     body: RcStmt<'a>,
     scope: Scope<'a>,
 }
 impl<'a> LoxFunction<'a> {
     pub fn new(
         name: Token<'a>,
-        paren: Box<[Token<'a>]>,
+        paren: Rc<[Symbol]>,
         body: RcStmt<'a>,
         scope: Scope<'a>,
     ) -> Self {
@@ -99,13 +126,20 @@ impl<'a> LoxFunction<'a> {
 
     fn set_arguments(&self, env: &mut Environment<'a>, args: &[Values<'a>]) {
         for (paran, args) in self.paren.iter().zip(args) {
-            env.define(paran.to_string(), args.clone());
+            env.define(*paran, args.clone());
         }
     }
 
     pub fn name(&self) -> &'a str {
         self.name.as_str()
     }
+    pub fn scope(&self) -> &Scope<'a> {
+        &self.scope
+    }
+    ///Approximate bytes used by the closure,not counting the scope it captured.
+    pub fn heap_size(&self) -> usize {
+        size_of::<Self>()
+    }
 }
 impl<'a> LoxCallable<'a> for LoxFunction<'a> {
     fn call(
@@ -114,34 +148,50 @@ impl<'a> LoxCallable<'a> for LoxFunction<'a> {
         args: &[Values<'a>],
     ) -> Result<Values<'a>, Box<dyn Error>> {
         let global_scope=env.get_current();
+        env.push_frame(Frame {
+            function: self.name,
+            call_line: env.call_line(),
+            caller: global_scope.clone(),
+            closure: self.scope.clone(),
+        }, args);
         env.set_scope(self.scope.clone());//Setting the pointer inside the function.So the variable
                                           //inside the function became avaliable.
         env.create_sub_values();
         self.set_arguments(env, args);
-        let return_value = self.body.execute(env)?;
-        env.delete_sub_values();
+        let return_value = self.body.execute(env);
+        let null = Values::Null;
+        env.pop_frame(match &return_value {
+            Ok(x) => Some(x.as_ref().unwrap_or(&null)),
+            Err(_) => None,
+        });
         env.set_scope(global_scope);//Returning from the function.Placing the pointer in hte gloabl
-                                    //position.
+                                    //position.It is done on errors too,so a host that catches
+                                    //the error(or `exit`) finds the scope as it was.
+        let return_value = return_value?;
         if let Some(return_value) = return_value {
             return Ok(return_value);
         }
-        return Ok(Values::Null);
+        Ok(Values::Null)
     }
     fn arity(&self, input_number: usize) -> bool {
         input_number == self.paren.len()
     }
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
 }
+#[allow(clippy::should_implement_trait)]
 impl Values<'_> {
     pub fn add(self, rhs: Self) -> Result<Self, String> {
         use Values::*;
         let ans = match (self, rhs) {
             (Number(x), Number(y)) => Number(x + y),
-            (Number(x), Str(y)) => Str(x.to_string() + &y),
+            (Number(x), Str(y)) => Str(format!("{}{}", x, y).into()),
             (Number(x), Boolean(y)) => Number(x + y),
             (Boolean(x), Number(y)) => Number(x + y),
             (Boolean(x), Boolean(y)) => Number(x + y),
-            (Str(x), Str(y)) => Str(x + &y),
-            (Str(x), Number(y)) => Str(x + &y.to_string()),
+            (Str(x), Str(y)) => Str(format!("{}{}", x, y).into()),
+            (Str(x), Number(y)) => Str(format!("{}{}", x, y).into()),
             (s, r) => return Err(format!("Can't add {} and {}", s, r)),
         };
         Ok(ans)
@@ -184,7 +234,11 @@ impl Values<'_> {
         match (self, rhs) {
             (Number(x), Number(y)) => x == y,
             (Boolean(x), Boolean(y)) => x == y,
-            (Str(x), Str(y)) => x == y,
+            (Str(x), Str(y)) => Rc::ptr_eq(x, y) || x == y,
+            (List(x), List(y)) => {
+                Rc::ptr_eq(x, y)
+                    || x.len() == y.len() && x.iter().zip(y.iter()).all(|(x, y)| x.eq(y).is_truthy())
+            }
             (Null, Null) => true,
             _ => false,
         }
@@ -195,7 +249,8 @@ impl Values<'_> {
         match (self, rhs) {
             (Number(x), Number(y)) => x != y,
             (Boolean(x), Boolean(y)) => x != y,
-            (Str(x), Str(y)) => x != y,
+            (Str(x), Str(y)) => !Rc::ptr_eq(x, y) && x != y,
+            (List(_), List(_)) => !self.eq(rhs).is_truthy(),
             (Null, Null) => false,
             _ => true,
         }
@@ -242,14 +297,54 @@ impl Values<'_> {
         Ok(ans.into())
     }
     pub fn is_truthy(&self) -> bool {
-        use Values::{Boolean, Fn, Null, Number, Str};
+        use Values::{Boolean, Fn, List, Null, Number, Str};
         match self {
             Boolean(x) | Number(x) => *x != 0.0,
             Str(x) => !x.is_empty(),
+            List(x) => !x.is_empty(),
             Null => false,
             Fn(_) => true,
         }
     }
+    ///Name of the type,as the script sees it.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Values::Str(_) => "string",
+            Values::Boolean(_) => "bool",
+            Values::Number(_) => "number",
+            Values::Fn(_) => "function",
+            Values::List(_) => "list",
+            Values::Null => "nil",
+        }
+    }
+    ///The value written the way it would appear in a script,strings are quoted and escaped.
+    pub fn repr(&self) -> String {
+        match self {
+            Values::Str(x) => format!("{:?}", x),
+            Values::Fn(x) => format!("<fn {}>", x.name()),
+            Values::List(x) => {
+                let parts: Vec<String> = x.iter().map(Values::repr).collect();
+                format!("[{}]", parts.join(", "))
+            }
+            x => x.to_string(),
+        }
+    }
+    ///Approximate bytes the value keeps on the heap,on top of its own size.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Values::Str(x) => x.len(),
+            Values::List(x) => x.iter().map(|x| size_of::<Self>() + x.heap_size()).sum(),
+            _ => 0,
+        }
+    }
+    ///Addresses of the heap objects the value points to,for the garbage collector.
+    ///A list can be shared by many scopes,so what is inside it isn't reported.The collector
+    ///then sees those functions as held from outside and keeps them.
+    pub fn references(&self, output: &mut Vec<usize>) {
+        if let Values::Fn(x) = self {
+            output.push(Rc::as_ptr(x) as *const () as usize);
+        }
+    }
     ///Cast a lox value to lox boolean value
     pub fn cast_to_boolean(&self) -> Self {
         self.is_truthy().into()
@@ -0,0 +1,120 @@
+use std::{fmt::Debug, marker::PhantomData, mem, rc::Rc};
+
+use super::{LoxCallable, Values};
+
+///Every bit of the exponent and the quiet bit set,plus one more so real NaN stay apart.
+const QNAN: u64 = 0x7ffc_0000_0000_0000;
+const SIGN: u64 = 0x8000_0000_0000_0000;
+const NULL: u64 = QNAN | 1;
+const FALSE: u64 = QNAN | 2;
+const TRUE: u64 = QNAN | 3;
+const POINTER_MASK: u64 = 0x0000_ffff_ffff_ffff;
+
+///The values that don't fit in 8 bytes.
+#[derive(Debug, Clone)]
+enum HeapObject<'a> {
+    Str(Rc<str>),
+    Fn(Rc<dyn LoxCallable<'a> + 'a>),
+    List(Rc<[Values<'a>]>),
+}
+
+///A lox value packed in 8 bytes.
+///Numbers are stored as they are,every other value hides in the unused bits of a NaN:
+///null and booleans are fixed bit patterns,strings,functions and lists are a pointer with the
+///sign bit set.
+///
+///The pointer is a `Rc<HeapObject>`,the values keep wide pointers that don't fit in 48 bits so
+///storing one takes an allocation,but clones only share it.A shared box still takes a reference
+///of its function for every copy,the garbage collector counts one for every place a function is
+///stored.
+pub struct NanBox<'a> {
+    bits: u64,
+    value: PhantomData<HeapObject<'a>>,
+}
+impl<'a> NanBox<'a> {
+    fn from_bits(bits: u64) -> Self {
+        Self {
+            bits,
+            value: PhantomData,
+        }
+    }
+    fn from_object(object: HeapObject<'a>) -> Self {
+        let pointer = Rc::into_raw(Rc::new(object)) as u64;
+        //Checked in every build,a cut pointer would be read as another address.
+        assert_eq!(pointer & !POINTER_MASK, 0, "pointer doesn't fit in 48 bits");
+        Self::from_bits(SIGN | QNAN | pointer)
+    }
+    fn object(&self) -> Option<&HeapObject<'a>> {
+        if self.bits & (SIGN | QNAN) != SIGN | QNAN {
+            return None;
+        }
+        let pointer = (self.bits & POINTER_MASK) as *const HeapObject<'a>;
+        //SAFETY:The pointer came from `Rc::into_raw` and this box holds one of its references.
+        Some(unsafe { &*pointer })
+    }
+    pub fn to_value(&self) -> Values<'a> {
+        if let Some(object) = self.object() {
+            return match object {
+                HeapObject::Str(x) => Values::Str(x.clone()),
+                HeapObject::Fn(x) => Values::Fn(x.clone()),
+                HeapObject::List(x) => Values::List(x.clone()),
+            };
+        }
+        match self.bits {
+            NULL => Values::Null,
+            FALSE => Values::Boolean(0.0),
+            TRUE => Values::Boolean(1.0),
+            x => Values::Number(f64::from_bits(x)),
+        }
+    }
+}
+impl<'a> From<Values<'a>> for NanBox<'a> {
+    fn from(value: Values<'a>) -> Self {
+        match value {
+            Values::Number(x) if x.is_nan() => Self::from_bits(f64::NAN.to_bits()),
+            Values::Number(x) => Self::from_bits(x.to_bits()),
+            Values::Boolean(0.0) => Self::from_bits(FALSE),
+            Values::Boolean(_) => Self::from_bits(TRUE),
+            Values::Null => Self::from_bits(NULL),
+            Values::Str(x) => Self::from_object(HeapObject::Str(x)),
+            Values::Fn(x) => Self::from_object(HeapObject::Fn(x)),
+            Values::List(x) => Self::from_object(HeapObject::List(x)),
+        }
+    }
+}
+impl<'a> From<&NanBox<'a>> for Values<'a> {
+    fn from(value: &NanBox<'a>) -> Self {
+        value.to_value()
+    }
+}
+impl Clone for NanBox<'_> {
+    fn clone(&self) -> Self {
+        if let Some(object) = self.object() {
+            if let HeapObject::Fn(x) = object {
+                mem::forget(x.clone());
+            }
+            //SAFETY:The pointer came from `Rc::into_raw` and is still alive.
+            unsafe { Rc::increment_strong_count(object) };
+        }
+        Self::from_bits(self.bits)
+    }
+}
+impl Drop for NanBox<'_> {
+    fn drop(&mut self) {
+        let Some(object) = self.object() else {
+            return;
+        };
+        let object = object as *const HeapObject;
+        //SAFETY:Same pointer as in `object`,the reference of this box is given back once.
+        let object = unsafe { Rc::from_raw(object) };
+        if let (HeapObject::Fn(x), 2..) = (&*object, Rc::strong_count(&object)) {
+            //SAFETY:`clone` took this reference of the function for the copy being dropped.
+            unsafe { Rc::decrement_strong_count(Rc::as_ptr(x)) };
+        }
+    }
+}
+impl Debug for NanBox<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.to_value(), f)
+    }
+}
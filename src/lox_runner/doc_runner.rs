@@ -0,0 +1,58 @@
+//!`rlox doc`,writing the documentation of lox files.
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{
+    doc::{document, functions, Format},
+    lox_runner::find_lox_files,
+};
+
+///Write the documentation of `path`,a `.lox` file or a directory searched recursively,to `out`
+///in `format`.Files that can't be read or parsed are reported to `errors`.Gives back whether
+///every file could be documented.
+pub fn document_files(
+    path: &Path,
+    format: Format,
+    out: &mut dyn Write,
+    errors: &mut dyn Write,
+) -> io::Result<bool> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        find_lox_files(path, &mut files)?;
+    } else {
+        files.push(path.to_path_buf());
+    }
+    let mut success = true;
+    for (i, file) in files.iter().enumerate() {
+        let code = match fs::read_to_string(file) {
+            Ok(x) => x,
+            Err(x) => {
+                writeln!(
+                    errors,
+                    "{}\n  ERROR can't read the file:{}",
+                    file.display(),
+                    x
+                )?;
+                success = false;
+                continue;
+            }
+        };
+        let documented = functions(&code).map_err(|x| x.to_string());
+        match documented {
+            Ok(x) => {
+                if i != 0 && format == Format::Markdown {
+                    writeln!(out)?;
+                }
+                write!(out, "{}", document(&file.display().to_string(), &x, format))?;
+            }
+            Err(x) => {
+                writeln!(errors, "{}\n  ERROR {}", file.display(), x.trim_end())?;
+                success = false;
+            }
+        }
+    }
+    Ok(success)
+}
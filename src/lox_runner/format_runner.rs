@@ -0,0 +1,56 @@
+//!`rlox fmt`,formatting lox files in place or,with `--check`,only reporting the ones that
+//!aren't formatted.
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{formatter::format_code, lox_runner::find_lox_files};
+
+///Format `path`,a `.lox` file or a directory searched recursively,reporting to `out`.Gives back
+///whether every file could be read and formatted,and with `check` whether every file already
+///was.
+pub fn format_files(path: &Path, check: bool, out: &mut dyn Write) -> io::Result<bool> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        find_lox_files(path, &mut files)?;
+    } else {
+        files.push(path.to_path_buf());
+    }
+    let mut success = true;
+    for file in files {
+        let code = match fs::read_to_string(&file) {
+            Ok(x) => x,
+            Err(x) => {
+                writeln!(out, "{}\n  ERROR can't read the file:{}", file.display(), x)?;
+                success = false;
+                continue;
+            }
+        };
+        let formatted = match format_code(&code) {
+            Ok(x) => x,
+            Err(x) => {
+                writeln!(
+                    out,
+                    "{}\n  ERROR {}",
+                    file.display(),
+                    x.to_string().trim_end()
+                )?;
+                success = false;
+                continue;
+            }
+        };
+        if formatted == code {
+            continue;
+        }
+        if check {
+            writeln!(out, "{} is not formatted", file.display())?;
+            success = false;
+        } else {
+            fs::write(&file, formatted)?;
+            writeln!(out, "Formatted {}", file.display())?;
+        }
+    }
+    Ok(success)
+}
@@ -0,0 +1,44 @@
+//!`rlox lint`,reporting the warnings of lox files.
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{lint::lint, lox_runner::find_lox_files};
+
+///Lint `path`,a `.lox` file or a directory searched recursively,writing every warning to `out`.
+///Gives back whether the files were read and compiled without any warning.
+pub fn lint_files(path: &Path, out: &mut dyn Write) -> io::Result<bool> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        find_lox_files(path, &mut files)?;
+    } else {
+        files.push(path.to_path_buf());
+    }
+    let mut clean = true;
+    for file in files {
+        let code = match fs::read_to_string(&file) {
+            Ok(x) => x,
+            Err(x) => {
+                writeln!(out, "{}\n  ERROR can't read the file:{}", file.display(), x)?;
+                clean = false;
+                continue;
+            }
+        };
+        let warnings = lint(&code).map_err(|x| x.to_string());
+        match warnings {
+            Ok(warnings) => {
+                for x in &warnings {
+                    writeln!(out, "{}:{}", file.display(), x)?;
+                }
+                clean &= warnings.is_empty();
+            }
+            Err(x) => {
+                writeln!(out, "{}\n  ERROR {}", file.display(), x.trim_end())?;
+                clean = false;
+            }
+        }
+    }
+    Ok(clean)
+}
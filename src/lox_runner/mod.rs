@@ -1,19 +1,42 @@
-use std::error::Error;
+pub mod doc_runner;
+pub mod format_runner;
+pub mod lint_runner;
+pub mod test_runner;
+
+use std::{
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use ascii::AsAsciiStr;
 
 use crate::ast::statement::Block;
 use crate::parser::Parser;
 use crate::token::Scanner;
+///Parse `code`,with constant expressions folded and dead branches removed.
 pub fn code_to_stblock<'a>(code:&'a str)->Result<Block<'a>,Box<dyn Error+'a>>{
-    let code=code.as_ascii_str().unwrap();
-    let token=match Scanner::new(code).scan_tokens(){
-        Ok(x)=>x,
-        Err(x)=>return Err(x.into()),
-    };
-    let ast=match Parser::new(&token).parse(){
-        Ok(x)=>x,
-        Err(x)=>return Err(x.into()),
-    };
-    Ok(ast.into())
+    code_to_stblock_with(code,true)
+}
+///Parse `code`,optimizing it only if `optimize` is set.
+pub fn code_to_stblock_with<'a>(code:&'a str,optimize:bool)->Result<Block<'a>,Box<dyn Error+'a>>{
+    let code=code.as_ascii_str()?;
+    let token=Scanner::new(code).scan_tokens()?;
+    let ast:Block=Parser::new(&token).parse()?.into();
+    Ok(if optimize { ast.optimized() } else { ast })
+}
+///Every `.lox` file under `dir`,sorted so the report is in the same order every time.
+pub(crate) fn find_lox_files(dir: &Path, output: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|x| x.map(|x| x.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            find_lox_files(&entry, output)?;
+        } else if entry.extension().is_some_and(|x| x == "lox") {
+            output.push(entry);
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,167 @@
+//!`rlox test`,running the `test_*` functions of lox files.
+//!
+//!Every test gets its own interpreter:the file is run from the top,then the test function is
+//!called.The top level runs once for every test on purpose,so a test can't see what another one
+//!changed,and what it prints or writes happens that many times.A runtime error,a failed `assert`
+//!or even a panic fails only that test.Tests may use the filesystem,like scripts run by `rlox`.
+//!The panic hook is left alone,a host that doesn't want the panic message on stderr sets its own.
+//!With coverage the files run without the optimizer,so the branches it would remove still count.
+use std::{
+    fs,
+    io::{self, Write},
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    ast::statement::StmtMetaData,
+    coverage::{FileCoverage, Recorder},
+    interpreter::Interpreter,
+    lox_runner::{code_to_stblock, code_to_stblock_with, find_lox_files},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+    pub elapsed: Duration,
+}
+impl Summary {
+    pub fn success(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+///Run the tests of `path`,a `.lox` file or a directory searched recursively,writing the report
+///to `out`.
+pub fn run_tests(path: &Path, out: &mut dyn Write) -> io::Result<Summary> {
+    run(path, out, None)
+}
+///Same as `run_tests`,also giving back the coverage of every file with tests.
+pub fn run_tests_with_coverage(
+    path: &Path,
+    out: &mut dyn Write,
+) -> io::Result<(Summary, Vec<FileCoverage>)> {
+    let mut coverage = Vec::new();
+    let summary = run(path, out, Some(&mut coverage))?;
+    Ok((summary, coverage))
+}
+fn run(
+    path: &Path,
+    out: &mut dyn Write,
+    mut coverage: Option<&mut Vec<FileCoverage>>,
+) -> io::Result<Summary> {
+    let start = Instant::now();
+    let mut files = Vec::new();
+    if path.is_dir() {
+        find_lox_files(path, &mut files)?;
+    } else {
+        files.push(path.to_path_buf());
+    }
+    let mut summary = Summary::default();
+    for file in files {
+        run_file(&file, out, &mut summary, coverage.as_deref_mut())?;
+    }
+    summary.elapsed = start.elapsed();
+    writeln!(
+        out,
+        "{} passed,{} failed in {:.2?}",
+        summary.passed, summary.failed, summary.elapsed
+    )?;
+    Ok(summary)
+}
+fn run_file(
+    path: &Path,
+    out: &mut dyn Write,
+    summary: &mut Summary,
+    coverage: Option<&mut Vec<FileCoverage>>,
+) -> io::Result<()> {
+    let code = match fs::read_to_string(path) {
+        Ok(x) => x,
+        Err(x) => {
+            writeln!(out, "{}\n  ERROR can't read the file:{}", path.display(), x)?;
+            summary.failed += 1;
+            return Ok(());
+        }
+    };
+    let names = match test_names(&code) {
+        Ok(x) => x,
+        Err(x) => {
+            writeln!(out, "{}\n  ERROR {}", path.display(), indent(x.trim_end()))?;
+            summary.failed += 1;
+            return Ok(());
+        }
+    };
+    if names.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "{}", path.display())?;
+    let recorder = coverage.is_some().then(Recorder::new);
+    for name in names {
+        let start = Instant::now();
+        let (result, output) = run_test(&code, &name, recorder.as_ref());
+        let elapsed = start.elapsed();
+        match result {
+            Ok(()) => {
+                summary.passed += 1;
+                writeln!(out, "  PASS {} ({:.2?})", name, elapsed)?;
+            }
+            Err(x) => {
+                summary.failed += 1;
+                writeln!(out, "  FAIL {} ({:.2?})", name, elapsed)?;
+                writeln!(out, "       {}", indent(&x))?;
+                if !output.is_empty() {
+                    writeln!(out, "       output:\n       {}", indent(output.trim_end()))?;
+                }
+            }
+        }
+    }
+    if let (Some(coverage), Some(recorder)) = (coverage, recorder) {
+        //The code parsed,`test_names` did it already.
+        if let Ok(ast) = code_to_stblock_with(&code, false) {
+            let mut file = FileCoverage::new(&path.display().to_string(), &ast);
+            file.add(&recorder);
+            coverage.push(file);
+        }
+    }
+    Ok(())
+}
+///Names of the top level functions starting with `test_`,in the order they are declared.
+fn test_names(code: &str) -> Result<Vec<String>, String> {
+    let ast = code_to_stblock(code).map_err(|x| x.to_string())?;
+    let names = ast.statements().iter().filter_map(|x| match x.metadata() {
+        StmtMetaData::Function { name } if name.as_str().starts_with("test_") => {
+            Some(name.as_str().to_string())
+        }
+        _ => None,
+    });
+    Ok(names.collect())
+}
+///Run the whole file and then call `name`,giving back the result and what the script printed.
+fn run_test(code: &str, name: &str, recorder: Option<&Recorder>) -> (Result<(), String>, String) {
+    let code = format!("{}\n{}();\n", code, name);
+    let mut output = Vec::new();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let ast = code_to_stblock_with(&code, recorder.is_none()).map_err(|x| x.to_string())?;
+        let mut interpreter = Interpreter::new(&mut output);
+        interpreter.set_filesystem_access(true);
+        if let Some(x) = recorder {
+            interpreter.set_statement_hook(Some(Box::new(x.clone())));
+        }
+        interpreter.try_interpret(&ast).map_err(|x| x.to_string())
+    }));
+    let result = result.unwrap_or_else(|x| {
+        let message = x
+            .downcast_ref::<&str>()
+            .map(|x| x.to_string())
+            .or_else(|| x.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(format!("The interpreter panicked:{}", message))
+    });
+    (result, String::from_utf8_lossy(&output).into_owned())
+}
+///Line up the lines after the first with the report.
+fn indent(text: &str) -> String {
+    text.replace('\n', "\n       ")
+}
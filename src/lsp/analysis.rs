@@ -0,0 +1,307 @@
+//!What the language server knows about one document:the errors of the scanner and the parser,
+//!and every name with the declaration it resolves to.
+use std::{collections::HashMap, ops::Range};
+
+use ascii::AsAsciiStr;
+
+use crate::{
+    ast::{
+        expression::{Expr, ExprView},
+        statement::{DynStmt, Stmt, StmtView},
+    },
+    parser::Parser,
+    token::{Scanner, Token},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub range: Range<usize>,
+    pub message: String,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefinitionKind {
+    Variable,
+    Function,
+    Parameter,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefinitionKind,
+    ///Byte range of the name where it is declared.
+    pub range: Range<usize>,
+    ///Parameter names,for functions.
+    pub parameters: Vec<String>,
+    ///The function the declaration is inside of,`None` at the top level.
+    pub parent: Option<usize>,
+}
+impl Definition {
+    ///How the declaration looks in the source,for hovers.
+    pub fn signature(&self) -> String {
+        match self.kind {
+            DefinitionKind::Function => {
+                format!("fun {}({})", self.name, self.parameters.join(", "))
+            }
+            DefinitionKind::Variable => format!("var {}", self.name),
+            DefinitionKind::Parameter => format!("parameter {}", self.name),
+        }
+    }
+}
+///A use of a name,reading or assigning it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub range: Range<usize>,
+    ///Index in `Analysis::definitions`,`None` for built-ins and names declared nowhere.
+    pub definition: Option<usize>,
+}
+#[derive(Debug, Default)]
+pub struct Analysis {
+    pub diagnostics: Vec<Diagnostic>,
+    pub definitions: Vec<Definition>,
+    pub references: Vec<Reference>,
+}
+impl Analysis {
+    pub fn new(text: &str) -> Self {
+        let mut output = Self::default();
+        let source = match text.as_ascii_str() {
+            Ok(x) => x,
+            Err(x) => {
+                let start = x.valid_up_to();
+                let end = start + text[start..].chars().next().map_or(0, char::len_utf8);
+                output.diagnostics.push(Diagnostic {
+                    range: start..end,
+                    message: "Only ASCII characters are supported.".to_string(),
+                });
+                return output;
+            }
+        };
+        let tokens = match Scanner::new(source).scan_tokens() {
+            Ok(x) => x,
+            Err(x) => {
+                output.diagnostics = x
+                    .iter()
+                    .map(|x| Diagnostic {
+                        range: x.range(),
+                        message: x.message(),
+                    })
+                    .collect();
+                return output;
+            }
+        };
+        match Parser::new(&tokens).parse() {
+            Ok(statements) => {
+                let mut resolver = Resolver {
+                    text,
+                    analysis: &mut output,
+                    scopes: vec![HashMap::new()],
+                    function: None,
+                    deferred: Vec::new(),
+                };
+                resolver.statements(&statements);
+                resolver.resolve_deferred();
+            }
+            Err(x) => {
+                output.diagnostics = x
+                    .iter()
+                    .map(|x| Diagnostic {
+                        //Errors at the end of the file have no token in the text.
+                        range: x.token().range_in(text).unwrap_or(text.len()..text.len()),
+                        message: x.message().to_string(),
+                    })
+                    .collect();
+            }
+        }
+        output
+    }
+    ///The declaration of the name at `offset`,whether `offset` is on a use or on the declaration.
+    pub fn definition_at(&self, offset: usize) -> Option<usize> {
+        let on = |x: &Range<usize>| x.start <= offset && offset <= x.end;
+        if let Some(x) = self.definitions.iter().position(|x| on(&x.range)) {
+            return Some(x);
+        }
+        self.references
+            .iter()
+            .find(|x| on(&x.range))
+            .and_then(|x| x.definition)
+    }
+    ///Every use of `definition`.
+    pub fn references_to(&self, definition: usize) -> impl Iterator<Item = &Reference> {
+        self.references
+            .iter()
+            .filter(move |x| x.definition == Some(definition))
+    }
+}
+
+struct Resolver<'t, 'o> {
+    text: &'t str,
+    analysis: &'o mut Analysis,
+    ///Names visible at this point,innermost scope last.
+    scopes: Vec<HashMap<String, usize>>,
+    function: Option<usize>,
+    ///Uses of a name inside a function that weren't declared yet.A global declared later is still
+    ///defined when the function runs,so they are looked up again in the globals at the end.
+    deferred: Vec<(usize, String)>,
+}
+impl<'a> Resolver<'_, '_> {
+    fn range(&self, token: Token<'a>) -> Option<Range<usize>> {
+        token.range_in(self.text)
+    }
+    fn declare(
+        &mut self,
+        name: Token<'a>,
+        kind: DefinitionKind,
+        parameters: Vec<String>,
+    ) -> Option<usize> {
+        let range = self.range(name)?;
+        let index = self.analysis.definitions.len();
+        self.analysis.definitions.push(Definition {
+            name: name.as_str().to_string(),
+            kind,
+            range,
+            parameters,
+            parent: self.function,
+        });
+        let scope = self.scopes.last_mut().unwrap();
+        scope.insert(name.as_str().to_string(), index);
+        Some(index)
+    }
+    fn reference(&mut self, name: Token<'a>) {
+        let Some(range) = self.range(name) else {
+            return;
+        };
+        let definition = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|x| x.get(name.as_str()).copied());
+        if definition.is_none() && self.function.is_some() {
+            self.deferred
+                .push((self.analysis.references.len(), name.as_str().to_string()));
+        }
+        self.analysis
+            .references
+            .push(Reference { range, definition });
+    }
+    fn resolve_deferred(&mut self) {
+        for (reference, name) in std::mem::take(&mut self.deferred) {
+            self.analysis.references[reference].definition = self.scopes[0].get(&name).copied();
+        }
+    }
+    fn statements(&mut self, statements: &[DynStmt<'a>]) {
+        for x in statements {
+            self.statement(x.as_ref());
+        }
+    }
+    fn scoped(&mut self, statements: &[DynStmt<'a>]) {
+        self.scopes.push(HashMap::new());
+        self.statements(statements);
+        self.scopes.pop();
+    }
+    fn statement(&mut self, statement: &(dyn Stmt<'a> + 'a)) {
+        match statement.view() {
+            StmtView::Expression(x) | StmtView::Return(x) => self.expression(x),
+            StmtView::Var { name, initializer } => {
+                //`var a = a;` reads the `a` from outside.
+                self.expression(initializer);
+                self.declare(name, DefinitionKind::Variable, Vec::new());
+            }
+            StmtView::Block(x) => self.scoped(x),
+            StmtView::Statements(x) => self.statements(x),
+            StmtView::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.expression(condition);
+                self.statement(then_branch);
+                if let Some(x) = else_branch {
+                    self.statement(x);
+                }
+            }
+            StmtView::While {
+                condition, body, ..
+            } => {
+                self.expression(condition);
+                self.statement(body);
+            }
+            StmtView::Function {
+                name,
+                parameters,
+                body,
+                ..
+            } => {
+                let names = parameters.iter().map(|x| x.as_str().to_string()).collect();
+                //Declared before the body,so the function can call itself.
+                let index = self.declare(name, DefinitionKind::Function, names);
+                let enclosing = self.function;
+                self.function = index.or(enclosing);
+                self.scopes.push(HashMap::new());
+                for x in parameters {
+                    self.declare(*x, DefinitionKind::Parameter, Vec::new());
+                }
+                self.statement(body);
+                self.scopes.pop();
+                self.function = enclosing;
+            }
+        }
+    }
+    fn expression(&mut self, expression: &(dyn Expr<'a> + 'a)) {
+        match expression.view() {
+            ExprView::Binary { left, right, .. } | ExprView::Logical { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            ExprView::Unary { right, .. } | ExprView::Grouping(right) => self.expression(right),
+            ExprView::Literal(_) | ExprView::Value(_) => (),
+            ExprView::Variable(name) => self.reference(name),
+            ExprView::Assign { name, value } => {
+                self.expression(value);
+                self.reference(name);
+            }
+            ExprView::Call {
+                callee, arguments, ..
+            } => {
+                self.expression(callee);
+                for x in arguments {
+                    self.expression(x.as_ref());
+                }
+            }
+        }
+    }
+}
+
+///Converts between byte offsets and the line and character positions of the protocol.
+///Characters are counted in UTF-16 code units,the default encoding of LSP.
+#[derive(Debug)]
+pub struct LineIndex<'t> {
+    text: &'t str,
+    line_starts: Vec<usize>,
+}
+impl<'t> LineIndex<'t> {
+    pub fn new(text: &'t str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(x, _)| x + 1));
+        Self { text, line_starts }
+    }
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|x| *x <= offset) - 1;
+        let start = self.line_starts[line];
+        let character = self.text[start..offset].encode_utf16().count();
+        (line, character)
+    }
+    pub fn offset(&self, line: usize, character: usize) -> usize {
+        let Some(start) = self.line_starts.get(line).copied() else {
+            return self.text.len();
+        };
+        let mut units = 0;
+        for (i, x) in self.text[start..].char_indices() {
+            if units >= character || x == '\n' {
+                return start + i;
+            }
+            units += x.len_utf16();
+        }
+        self.text.len()
+    }
+}
@@ -0,0 +1,314 @@
+//!A language server for lox,speaking JSON-RPC over a reader and a writer,usually stdin and
+//!stdout of the `rlox-lsp` binary.
+//!
+//!Documents are synced whole.After every change the scanner and the parser run again,their
+//!errors are published as diagnostics,and the names are resolved for go to definition,find
+//!references,hover and document symbols.
+pub mod analysis;
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Read, Write},
+    ops::Range,
+};
+
+use analysis::{Analysis, DefinitionKind, LineIndex};
+
+use crate::json::{self, Json};
+
+const METHOD_NOT_FOUND: f64 = -32601.0;
+const INVALID_PARAMS: f64 = -32602.0;
+const PARSE_ERROR: f64 = -32700.0;
+///Longest message body read,a bigger `Content-Length` is refused before reading it.
+const MAX_MESSAGE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct Server {
+    documents: HashMap<String, String>,
+    shutdown: bool,
+}
+impl Server {
+    ///Serve until the client sends `exit` or closes `input`.Gives back the exit code the protocol
+    ///asks for:0 if `shutdown` came first,1 if not.
+    pub fn run(&mut self, mut input: impl BufRead, output: &mut dyn Write) -> io::Result<i32> {
+        while let Some(body) = read_message(&mut input)? {
+            let message = match json::parse(&body) {
+                Ok(x) => x,
+                Err(x) => {
+                    let error = error_response(Json::Null, PARSE_ERROR, &x.to_string());
+                    write_message(output, &error)?;
+                    continue;
+                }
+            };
+            let method = message.get("method").and_then(Json::as_str).unwrap_or("");
+            if method == "exit" {
+                return Ok(if self.shutdown { 0 } else { 1 });
+            }
+            let params = message.get("params").cloned().unwrap_or(Json::Null);
+            match message.get("id") {
+                //A request,it needs an answer.
+                Some(id) => {
+                    let response = match self.request(method, &params) {
+                        Ok(result) => Json::object([
+                            ("jsonrpc", "2.0".into()),
+                            ("id", id.clone()),
+                            ("result", result),
+                        ]),
+                        Err((code, message)) => error_response(id.clone(), code, &message),
+                    };
+                    write_message(output, &response)?;
+                }
+                None => {
+                    for x in self.notification(method, &params) {
+                        write_message(output, &x)?;
+                    }
+                }
+            }
+        }
+        Ok(if self.shutdown { 0 } else { 1 })
+    }
+    fn request(&mut self, method: &str, params: &Json) -> Result<Json, (f64, String)> {
+        match method {
+            "initialize" => Ok(Json::object([
+                (
+                    "capabilities",
+                    Json::object([
+                        //Full document sync.
+                        ("textDocumentSync", 1.0.into()),
+                        ("definitionProvider", true.into()),
+                        ("referencesProvider", true.into()),
+                        ("hoverProvider", true.into()),
+                        ("documentSymbolProvider", true.into()),
+                    ]),
+                ),
+                ("serverInfo", Json::object([("name", "rlox-lsp".into())])),
+            ])),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            }
+            "textDocument/definition" => {
+                let (uri, text, offset) = self.position(params)?;
+                let analysis = Analysis::new(text);
+                let index = LineIndex::new(text);
+                Ok(match analysis.definition_at(offset) {
+                    Some(x) => location(uri, &index, &analysis.definitions[x].range),
+                    None => Json::Null,
+                })
+            }
+            "textDocument/references" => {
+                let (uri, text, offset) = self.position(params)?;
+                let analysis = Analysis::new(text);
+                let index = LineIndex::new(text);
+                let Some(definition) = analysis.definition_at(offset) else {
+                    return Ok(Json::Array(Vec::new()));
+                };
+                let include_declaration = params
+                    .get("context")
+                    .and_then(|x| x.get("includeDeclaration"))
+                    .and_then(Json::as_bool)
+                    .unwrap_or(true);
+                let mut locations = Vec::new();
+                if include_declaration {
+                    let range = &analysis.definitions[definition].range;
+                    locations.push(location(uri, &index, range));
+                }
+                for x in analysis.references_to(definition) {
+                    locations.push(location(uri, &index, &x.range));
+                }
+                Ok(Json::Array(locations))
+            }
+            "textDocument/hover" => {
+                let (_, text, offset) = self.position(params)?;
+                let analysis = Analysis::new(text);
+                let Some(definition) = analysis.definition_at(offset) else {
+                    return Ok(Json::Null);
+                };
+                let signature = analysis.definitions[definition].signature();
+                Ok(Json::object([(
+                    "contents",
+                    Json::object([
+                        ("kind", "markdown".into()),
+                        ("value", format!("```lox\n{}\n```", signature).into()),
+                    ]),
+                )]))
+            }
+            "textDocument/documentSymbol" => {
+                let (_, text) = self.document(params)?;
+                let analysis = Analysis::new(text);
+                let index = LineIndex::new(text);
+                Ok(Json::Array(symbols(&analysis, &index, None)))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method {}.", method))),
+        }
+    }
+    ///Handle a notification,giving back the notifications to send to the client.
+    fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        let document = params.get("textDocument");
+        let Some(uri) = document.and_then(|x| x.get("uri")).and_then(Json::as_str) else {
+            return Vec::new();
+        };
+        let text = match method {
+            "textDocument/didOpen" => document.and_then(|x| x.get("text")),
+            //With full sync the last change is the whole document.
+            "textDocument/didChange" => params
+                .get("contentChanges")
+                .and_then(Json::as_array)
+                .and_then(|x| x.last())
+                .and_then(|x| x.get("text")),
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![diagnostics(uri, "", &Analysis::default())];
+            }
+            _ => None,
+        };
+        let Some(text) = text.and_then(Json::as_str) else {
+            return Vec::new();
+        };
+        self.documents.insert(uri.to_string(), text.to_string());
+        vec![diagnostics(uri, text, &Analysis::new(text))]
+    }
+    fn document<'s>(&'s self, params: &'s Json) -> Result<(&'s str, &'s str), (f64, String)> {
+        let uri = params
+            .get("textDocument")
+            .and_then(|x| x.get("uri"))
+            .and_then(Json::as_str)
+            .ok_or((INVALID_PARAMS, "Missing textDocument.uri.".to_string()))?;
+        match self.documents.get(uri) {
+            Some(x) => Ok((uri, x)),
+            None => Err((INVALID_PARAMS, format!("{} is not open.", uri))),
+        }
+    }
+    ///The document and the byte offset of the `position` in `params`.
+    fn position<'s>(
+        &'s self,
+        params: &'s Json,
+    ) -> Result<(&'s str, &'s str, usize), (f64, String)> {
+        let (uri, text) = self.document(params)?;
+        let position = params.get("position");
+        let field = |name| {
+            position
+                .and_then(|x| x.get(name))
+                .and_then(Json::as_f64)
+                .ok_or((INVALID_PARAMS, format!("Missing position.{}.", name)))
+        };
+        let (line, character) = (field("line")?, field("character")?);
+        let offset = LineIndex::new(text).offset(line as usize, character as usize);
+        Ok((uri, text, offset))
+    }
+}
+
+fn error_response(id: Json, code: f64, message: &str) -> Json {
+    Json::object([
+        ("jsonrpc", "2.0".into()),
+        ("id", id),
+        (
+            "error",
+            Json::object([("code", code.into()), ("message", message.into())]),
+        ),
+    ])
+}
+fn range(index: &LineIndex, range: &Range<usize>) -> Json {
+    let position = |offset| {
+        let (line, character) = index.position(offset);
+        Json::object([("line", line.into()), ("character", character.into())])
+    };
+    Json::object([
+        ("start", position(range.start)),
+        ("end", position(range.end)),
+    ])
+}
+fn location(uri: &str, index: &LineIndex, span: &Range<usize>) -> Json {
+    Json::object([("uri", uri.into()), ("range", range(index, span))])
+}
+fn diagnostics(uri: &str, text: &str, analysis: &Analysis) -> Json {
+    let index = LineIndex::new(text);
+    let diagnostics = analysis.diagnostics.iter().map(|x| {
+        Json::object([
+            ("range", range(&index, &x.range)),
+            //Error.
+            ("severity", 1.0.into()),
+            ("source", "rlox".into()),
+            ("message", x.message.as_str().into()),
+        ])
+    });
+    Json::object([
+        ("jsonrpc", "2.0".into()),
+        ("method", "textDocument/publishDiagnostics".into()),
+        (
+            "params",
+            Json::object([
+                ("uri", uri.into()),
+                ("diagnostics", Json::Array(diagnostics.collect())),
+            ]),
+        ),
+    ])
+}
+///Document symbols for the declarations inside `parent`,functions hold their own declarations.
+///Parameters are left out.
+fn symbols(analysis: &Analysis, index: &LineIndex, parent: Option<usize>) -> Vec<Json> {
+    let children = analysis.definitions.iter().enumerate();
+    children
+        .filter(|(_, x)| x.parent == parent && x.kind != DefinitionKind::Parameter)
+        .map(|(i, x)| {
+            let (kind, detail) = match x.kind {
+                //Function and Variable in the SymbolKind of the protocol.
+                DefinitionKind::Function => (12.0, x.signature()),
+                _ => (13.0, String::new()),
+            };
+            Json::object([
+                ("name", x.name.as_str().into()),
+                ("detail", detail.into()),
+                ("kind", kind.into()),
+                ("range", range(index, &x.range)),
+                ("selectionRange", range(index, &x.range)),
+                ("children", Json::Array(symbols(analysis, index, Some(i)))),
+            ])
+        })
+        .collect()
+}
+
+///Read one message framed by a `Content-Length` header,`None` when the input ended.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message without a Content-Length header",
+        ));
+    };
+    if length > MAX_MESSAGE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {} bytes,more than {}", length, MAX_MESSAGE),
+        ));
+    }
+    let mut body = Vec::new();
+    input.take(length as u64).read_to_end(&mut body)?;
+    if body.len() < length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|x| io::Error::new(io::ErrorKind::InvalidData, x))
+}
+pub fn write_message(output: &mut dyn Write, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
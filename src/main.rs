@@ -1,19 +1,246 @@
-use std::{env, fs, io::stdout};
+use std::{
+    env, fs,
+    io::{stderr, stdout, Write},
+    panic,
+    path::Path,
+    process,
+};
 
-use rlox::{interpreter::Interpreter, lox_runner::code_to_stblock};
+use rlox::{
+    coverage::{write_lcov, write_summary},
+    doc::Format as DocFormat,
+    highlight::{highlight, Format},
+    interpreter::{process::Exit, Interpreter},
+    lox_runner::{
+        code_to_stblock_with,
+        doc_runner::document_files,
+        format_runner::format_files,
+        lint_runner::lint_files,
+        test_runner::{run_tests, run_tests_with_coverage},
+    },
+    profiler::Profiler,
+    trace::Tracer,
+};
+
+const USAGE: &str = "Usage:rlox [--no-optimize] [--profile] [--folded file] [--trace] [--trace-file file] [--trace-function name] script [args]";
 
 fn main() {
     let mut stdout = stdout();
-    let args: Vec<String> = env::args().collect();
-    let _path = match args.get(1) {
-        Some(x) => x.as_str(),
-        None => "",
+    let mut args: Vec<String> = env::args().collect();
+    //Options come before the script,what follows the script is for the script.
+    let mut optimize = true;
+    let mut profiler = None;
+    let mut folded = None;
+    //The trace goes to stderr unless `--trace-file` names a file.
+    let mut trace = false;
+    let mut trace_file = None;
+    let mut trace_function = None;
+    while args.get(1).is_some_and(|x| x.starts_with("--")) {
+        match args.remove(1).as_str() {
+            //Runs the code as written.
+            "--no-optimize" => optimize = false,
+            "--profile" => profiler = Some(Profiler::new()),
+            "--folded" if args.len() > 1 => {
+                profiler = Some(Profiler::new());
+                folded = Some(args.remove(1));
+            }
+            "--trace" => trace = true,
+            "--trace-file" if args.len() > 1 => {
+                trace = true;
+                trace_file = Some(args.remove(1));
+            }
+            "--trace-function" if args.len() > 1 => {
+                trace = true;
+                trace_function = Some(args.remove(1));
+            }
+            x => {
+                eprintln!("Unknown option {}.", x);
+                eprintln!("{}", USAGE);
+                process::exit(64);
+            }
+        }
+    }
+    if trace && profiler.is_some() {
+        eprintln!("--trace and --profile can't be used together.");
+        process::exit(64);
+    }
+    if args.get(1).map(String::as_str) == Some("test") {
+        //`rlox test [--coverage] [--lcov file] [path]`,`--lcov` implies `--coverage`.
+        let mut coverage = false;
+        let mut lcov = None;
+        let mut path = ".";
+        let mut options = args.iter().skip(2);
+        while let Some(x) = options.next() {
+            match x.as_str() {
+                "--coverage" => coverage = true,
+                "--lcov" => {
+                    coverage = true;
+                    lcov = options.next();
+                }
+                x => path = x,
+            }
+        }
+        //A panic is reported as the failure of its test,the default message would only add noise.
+        panic::set_hook(Box::new(|_| {}));
+        let result = match coverage {
+            true => run_tests_with_coverage(Path::new(path), &mut stdout).and_then(
+                |(summary, files)| {
+                    write_summary(&files, &mut stdout)?;
+                    if let Some(lcov) = lcov {
+                        let written =
+                            fs::File::create(lcov).and_then(|mut x| write_lcov(&files, &mut x));
+                        if let Err(x) = written {
+                            eprintln!("Can't write {}:{}", lcov, x);
+                            process::exit(74);
+                        }
+                    }
+                    Ok(summary)
+                },
+            ),
+            false => run_tests(Path::new(path), &mut stdout),
+        };
+        match result {
+            Ok(x) if x.success() => process::exit(0),
+            Ok(_) => process::exit(1),
+            Err(x) => {
+                eprintln!("Can't run the tests in {}:{}", path, x);
+                process::exit(74);
+            }
+        }
+    }
+    if args.get(1).map(String::as_str) == Some("fmt") {
+        let check = args.iter().any(|x| x == "--check");
+        let path = args.iter().skip(2).find(|x| *x != "--check");
+        let path = path.map_or(".", String::as_str);
+        match format_files(Path::new(path), check, &mut stdout) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(x) => {
+                eprintln!("Can't format {}:{}", path, x);
+                process::exit(74);
+            }
+        }
+    }
+    if args.get(1).map(String::as_str) == Some("highlight") {
+        //`rlox highlight [--html] file`,ANSI colours unless `--html`.
+        let html = args.iter().any(|x| x == "--html");
+        let Some(path) = args.iter().skip(2).find(|x| *x != "--html") else {
+            eprintln!("Usage:rlox highlight [--html] file");
+            process::exit(64);
+        };
+        let code = match fs::read_to_string(path) {
+            Ok(x) => x,
+            Err(x) => {
+                eprintln!("Can't read {}:{}", path, x);
+                process::exit(74);
+            }
+        };
+        let format = match html {
+            true => Format::Html,
+            false => Format::Ansi,
+        };
+        match highlight(&code, format) {
+            Ok(x) => print!("{}", x),
+            Err(x) => {
+                eprintln!("{}", x);
+                process::exit(65);
+            }
+        }
+        process::exit(0);
+    }
+    if args.get(1).map(String::as_str) == Some("doc") {
+        //`rlox doc [--html] [path]`,Markdown unless `--html`.
+        let format = match args.iter().any(|x| x == "--html") {
+            true => DocFormat::Html,
+            false => DocFormat::Markdown,
+        };
+        let path = args.iter().skip(2).find(|x| *x != "--html");
+        let path = path.map_or(".", String::as_str);
+        match document_files(Path::new(path), format, &mut stdout, &mut stderr()) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(x) => {
+                eprintln!("Can't document {}:{}", path, x);
+                process::exit(74);
+            }
+        }
+    }
+    if args.get(1).map(String::as_str) == Some("lint") {
+        let path = args.get(2).map_or(".", String::as_str);
+        match lint_files(Path::new(path), &mut stdout) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(x) => {
+                eprintln!("Can't lint {}:{}", path, x);
+                process::exit(74);
+            }
+        }
+    }
+    let Some(path) = args.get(1) else {
+        eprintln!("{}", USAGE);
+        process::exit(64);
+    };
+    let code = match fs::read_to_string(path) {
+        Ok(x) => x,
+        Err(x) => {
+            eprintln!("Can't read {}:{}", path, x);
+            process::exit(74);
+        }
     };
-    let path = "tst2.lox";
-    let code = fs::read_to_string(path).unwrap();
 
     let mut interpreter = Interpreter::new(&mut stdout);
-    let ast = code_to_stblock(&code).unwrap();
+    interpreter.set_filesystem_access(true);
+    interpreter.set_env_vars(None);
+    //The main thread has a bigger stack than the threads the default is made for.
+    interpreter.set_max_call_depth(Some(1000));
+    interpreter.set_args(args.iter().skip(2).cloned().collect());
+    if let Some(x) = &profiler {
+        interpreter.set_statement_hook(Some(Box::new(x.clone())));
+    }
+    if trace {
+        let out: Box<dyn Write> = match &trace_file {
+            Some(path) => match fs::File::create(path) {
+                Ok(x) => Box::new(x),
+                Err(x) => {
+                    eprintln!("Can't write {}:{}", path, x);
+                    process::exit(74);
+                }
+            },
+            None => Box::new(stderr()),
+        };
+        let mut tracer = Tracer::new(out, &code);
+        tracer.set_function(trace_function);
+        interpreter.set_statement_hook(Some(Box::new(tracer)));
+    }
+    let ast = match code_to_stblock_with(&code, optimize) {
+        Ok(x) => x,
+        Err(x) => {
+            eprintln!("{}", x);
+            process::exit(65);
+        }
+    };
     //println!("{:?}",ast);
-    interpreter.interpret(&ast);
+    let result = interpreter.try_interpret(&ast);
+    if let Some(profiler) = profiler {
+        //The report goes to stderr,to keep it apart from what the script prints.
+        if let Err(x) = profiler.report(&mut stderr()) {
+            eprintln!("Can't write the profile:{}", x);
+        }
+        if let Some(path) = folded {
+            let written = fs::File::create(&path).and_then(|mut x| profiler.write_folded(&mut x));
+            if let Err(x) = written {
+                eprintln!("Can't write {}:{}", path, x);
+                process::exit(74);
+            }
+        }
+    }
+    if let Err(x) = result {
+        match x.downcast_ref::<Exit>() {
+            Some(Exit(code)) => process::exit(*code),
+            None => {
+                eprintln!("{}", x);
+                process::exit(70);
+            }
+        }
+    }
 }
@@ -9,7 +9,7 @@ use crate::{
         statement::{Block, DynStmt, Expression, FunctionDelc, If, ReturnStmt, Stmt, Var, WhileStmt},
     },
     lox_error::Errors,
-    lox_object::{LoxFunction, Values},
+    lox_object::Values,
     token::{Token, TokenType},
 };
 #[derive(Debug, Clone)]
@@ -31,7 +31,7 @@ enum ParserErrorType {
     MissingIdentifier(&'static str),
 }
 impl ParserErrorType {
-    fn to_str(&self) -> &'static str {
+    fn as_str(&self) -> &'static str {
         match self {
             Self::MissingSemicolon => "Semicolon \";\" missing after statement.",
             Self::InvalidAssignment => "Right side of the assignment is not a variable.",
@@ -41,7 +41,7 @@ impl ParserErrorType {
             Self::MissingVariable => "Variable not found",
             Self::MissingRightBrace => "Right Brace \"}\" is missing",
             Self::MissingLeftBrace => "Left Brace \"{\" is missing",
-            Self::MissingIdentifier(x) => "Missing Identifier",
+            Self::MissingIdentifier(_x) => "Missing Identifier",
         }
     }
 }
@@ -57,7 +57,7 @@ type Result<'b> = std::result::Result<Stmts<'b>, ParserErrors<'b>>;
 impl Display for ParserError<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let pos = self.pos.span();
-        let error_str = self.error_type.to_str();
+        let error_str = self.error_type.as_str();
         write!(f, "Error in {}\n Error:{}", pos, error_str)
     }
 }
@@ -65,6 +65,13 @@ impl<'a> ParserError<'a> {
     fn new(pos: Token<'a>, error_type: ParserErrorType) -> Self {
         Self { pos, error_type }
     }
+    ///The token the parser was looking at when it found the error.
+    pub fn token(&self) -> Token<'a> {
+        self.pos
+    }
+    pub fn message(&self) -> &'static str {
+        self.error_type.as_str()
+    }
 }
 ///program → declaration* EOF ;
 ///declaration → varDecl  | statement ;
@@ -137,7 +144,7 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
             let right = self.and();
             expr = Box::new(Logical::new(expr, operator, right));
         }
-        return expr;
+        expr
     }
     /// equiltiy->comparasion ("!="|"!" comparasion)*
     fn equility(&mut self) -> Box<dyn Expr<'b> + 'b> {
@@ -284,7 +291,7 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
     fn expression_statement(&mut self) -> Box<dyn Stmt<'b> + 'b> {
         let expr = self.expression();
         self.consume(TokenType::Semicolon, ParserErrorType::MissingSemicolon);
-        return Box::new(Expression::new(expr));
+        Box::new(Expression::new(expr))
     }
 
     fn declaration(&mut self) -> Box<dyn Stmt<'b> + 'b> {
@@ -326,22 +333,26 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
     }
 
     fn block_statement(&mut self) -> DynStmt<'b> {
+        Box::new(self.block())
+    }
+    fn block(&mut self) -> Block<'b> {
         let mut statements: Vec<Box<dyn Stmt>> = Vec::new();
         while !self.checks(&[TokenType::RightBrace]) && !self.is_eof() {
             statements.push(self.declaration());
         }
         self.consume(TokenType::RightBrace, ParserErrorType::MissingRightBrace);
-        Box::new(Block::from(statements))
+        Block::from(statements)
     }
 
     fn checks(&self, token_types: &[TokenType]) -> bool {
         let Some(token) = self.current_token() else {
             return false;
         };
-        return token.matches_token(token_types);
+        token.matches_token(token_types)
     }
 
     fn if_statement(&mut self) -> DynStmt<'b> {
+        let keyword = self.previous_token();
         self.consume(TokenType::LeftParen, ParserErrorType::MissingLeftParen);
         let condition = self.expression();
         self.consume(TokenType::RightParen, ParserErrorType::MissingRightParen);
@@ -351,7 +362,7 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
         } else {
             None
         };
-        return Box::new(If::new(condition, then_b, else_b));
+        Box::new(If::new(keyword, condition, then_b, else_b))
     }
 
     fn match_with(&mut self, token_type: TokenType) -> bool {
@@ -363,7 +374,7 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
             self.advance();
             return true;
         }
-        return false;
+        false
     }
 
     fn and(&mut self) -> DynExpr<'b> {
@@ -377,14 +388,16 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
     }
 
     fn while_statement(&mut self) -> DynStmt<'b> {
+        let keyword = self.previous_token();
         self.consume(TokenType::LeftParen, ParserErrorType::MissingLeftParen);
         let condition = self.expression();
         self.consume(TokenType::RightParen, ParserErrorType::MissingRightParen);
         let body = self.statement();
-        Box::new(WhileStmt::new(condition, body))
+        Box::new(WhileStmt::new(keyword, condition, body))
     }
 
     fn for_statement(&mut self) -> DynStmt<'b> {
+        let keyword = self.previous_token();
         self.consume(TokenType::LeftParen, ParserErrorType::MissingLeftParen);
         let initializer = if self.match_with(TokenType::Semicolon) {
             None
@@ -417,9 +430,9 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
             while_body.push(Box::new(increment));
         }
         let while_body = Box::new(Block::from(while_body));
-        let while_body = WhileStmt::new(condition, while_body);
+        let while_body = WhileStmt::new(keyword, condition, while_body);
         output_body.push(Box::new(while_body));
-        return Box::new(Block::from(output_body));
+        Box::new(Block::from(output_body))
     }
 
     fn check(&self, token_type: TokenType) -> bool {
@@ -438,7 +451,7 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
                 break;
             }
         }
-        return expr;
+        expr
     }
 
     fn finishcall(&mut self, callee: DynExpr<'b>) -> DynExpr<'b> {
@@ -453,7 +466,7 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
         }
 
         let paren = self.consume(TokenType::RightParen, ParserErrorType::MissingRightParen);
-        return Box::new(CallExpr::new(callee, paren, arguments.into()));
+        Box::new(CallExpr::new(callee, paren, arguments.into()))
     }
 
     fn recovery(&mut self) {
@@ -471,6 +484,8 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
     }
 
     fn function(&mut self, name: &'static str) -> DynStmt<'b> {
+        //The first token of the source has no line of code before its comments.
+        let doc = self.previous_token().doc_comment(self.index > 1);
         let name = self.consume(
             TokenType::Identifier,
             ParserErrorType::MissingIdentifier(name),
@@ -491,8 +506,8 @@ impl<'a, 'b: 'a> Parser<'a, 'b> {
         let parameter = parameters.into_boxed_slice();
         self.consume(TokenType::RightParen, ParserErrorType::MissingRightParen);
         self.consume(TokenType::LeftBrace, ParserErrorType::MissingLeftBrace);
-        let body = self.block_statement();
-        return Box::new(FunctionDelc::new(name, parameter, body));
+        let body = self.block();
+        Box::new(FunctionDelc::new(name, parameter, body, doc))
     }
 
     fn return_statement(&mut self) -> DynStmt<'b> {
@@ -0,0 +1,246 @@
+//!`rlox --profile`,timing the lox functions and the lines of a run through the statement hook.
+//!
+//!Time is measured with an `Instant` of the profiler,not the clock the script reads,so it is
+//!monotonic and a host that fakes the time of the script still gets real timings.The time between
+//!two statements goes to the line of the first one,and the time of a call to the function called.
+//!Built-ins are part of the line and the function calling them.
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    io::{self, Write},
+    rc::Rc,
+    time::Instant,
+};
+
+use crate::{
+    interpreter::{debug::StatementHook, environment::Environment},
+    lox_object::Values,
+};
+
+///Name the report gives to the code outside of any function.
+const MAIN: &str = "main";
+///How many lines the report lists.
+const HOT_LINES: usize = 10;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FunctionProfile {
+    pub name: String,
+    ///Line of the declaration,0 for `main`.
+    pub line: usize,
+    pub calls: u64,
+    ///Seconds spent in the function and the functions it called.A recursive call isn't counted
+    ///twice.
+    pub inclusive: f64,
+    ///Seconds spent in the function itself.
+    pub exclusive: f64,
+}
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineProfile {
+    pub line: usize,
+    ///How many statements on the line ran.
+    pub count: u64,
+    pub time: f64,
+}
+
+///A call that hasn't returned yet.
+#[derive(Debug)]
+struct Call {
+    function: usize,
+    start: f64,
+    ///Time spent in the calls it made.
+    children: f64,
+}
+#[derive(Debug, Default)]
+struct Data {
+    ///When the first event happened,the times below are seconds since then.
+    origin: Option<Instant>,
+    functions: Vec<FunctionProfile>,
+    index: HashMap<(String, usize), usize>,
+    lines: BTreeMap<usize, LineProfile>,
+    calls: Vec<Call>,
+    ///Exclusive time of each stack of calls,the way flame graphs want it.
+    stacks: BTreeMap<Vec<usize>, f64>,
+    start: Option<f64>,
+    end: f64,
+    ///Time spent in calls made outside of any function.
+    called_from_main: f64,
+    ///The line running and since when.
+    current: Option<(usize, f64)>,
+}
+impl Data {
+    fn now(&mut self) -> f64 {
+        self.origin
+            .get_or_insert_with(Instant::now)
+            .elapsed()
+            .as_secs_f64()
+    }
+    ///Give the time since the last event to the line running,and start the next line at `line`.
+    fn tick(&mut self, now: f64, line: usize) {
+        self.start.get_or_insert(now);
+        self.end = now;
+        if let Some((line, since)) = self.current {
+            self.lines.entry(line).or_default().time += now - since;
+        }
+        self.current = Some((line, now));
+    }
+}
+
+///Collects the profile of the runs it is the statement hook of.Clones share the same profile,so
+///the host keeps one to read it.
+#[derive(Debug, Clone, Default)]
+pub struct Profiler(Rc<RefCell<Data>>);
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    ///Seconds from the first statement to the last event.
+    pub fn total(&self) -> f64 {
+        let data = self.0.borrow();
+        data.start.map_or(0.0, |x| data.end - x)
+    }
+    ///Every function that was called and `main`,the most exclusive time first.
+    pub fn functions(&self) -> Vec<FunctionProfile> {
+        let total = self.total();
+        let data = self.0.borrow();
+        let mut output = data.functions.clone();
+        output.push(FunctionProfile {
+            name: MAIN.to_string(),
+            line: 0,
+            calls: 1,
+            inclusive: total,
+            exclusive: total - data.called_from_main,
+        });
+        output.sort_by(|x, y| {
+            y.exclusive
+                .total_cmp(&x.exclusive)
+                .then(y.calls.cmp(&x.calls))
+        });
+        output
+    }
+    ///Every line that ran,the most time first.
+    pub fn lines(&self) -> Vec<LineProfile> {
+        let data = self.0.borrow();
+        let mut output: Vec<_> = data
+            .lines
+            .iter()
+            .map(|(line, x)| LineProfile {
+                line: *line,
+                ..x.clone()
+            })
+            .collect();
+        output.sort_by(|x, y| y.time.total_cmp(&x.time).then(y.count.cmp(&x.count)));
+        output
+    }
+    pub fn report(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "Profile:{:.6} seconds", self.total())?;
+        writeln!(
+            out,
+            "{:>10} {:>12} {:>12}  function",
+            "calls", "inclusive", "exclusive"
+        )?;
+        for x in self.functions() {
+            let name = match x.line {
+                0 => x.name,
+                line => format!("{} (line {})", x.name, line),
+            };
+            writeln!(
+                out,
+                "{:>10} {:>12.6} {:>12.6}  {}",
+                x.calls, x.inclusive, x.exclusive, name
+            )?;
+        }
+        writeln!(out, "Hot lines:")?;
+        writeln!(out, "{:>10} {:>12}  line", "count", "time")?;
+        for x in self.lines().into_iter().take(HOT_LINES) {
+            writeln!(out, "{:>10} {:>12.6}  {}", x.count, x.time, x.line)?;
+        }
+        Ok(())
+    }
+    ///Write the stacks in the folded format of flame graph tools,one `main;f:1;g:5 micros` line
+    ///for each stack of calls with the microseconds spent in its innermost function.
+    pub fn write_folded(&self, out: &mut dyn Write) -> io::Result<()> {
+        let functions = self.functions();
+        let main = functions
+            .iter()
+            .find(|x| x.line == 0)
+            .map_or(0.0, |x| x.exclusive);
+        let data = self.0.borrow();
+        let mut stacks = vec![(MAIN.to_string(), main)];
+        for (stack, time) in &data.stacks {
+            let mut name = MAIN.to_string();
+            for x in stack {
+                let x = &data.functions[*x];
+                name.push_str(&format!(";{}:{}", x.name, x.line));
+            }
+            stacks.push((name, *time));
+        }
+        for (name, time) in stacks {
+            writeln!(out, "{} {}", name, (time * 1e6).round() as u64)?;
+        }
+        Ok(())
+    }
+}
+impl<'a> StatementHook<'a> for Profiler {
+    fn before_statement(
+        &mut self,
+        line: usize,
+        _env: &Environment<'a>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut data = self.0.borrow_mut();
+        let now = data.now();
+        data.tick(now, line);
+        data.lines.entry(line).or_default().count += 1;
+        Ok(())
+    }
+    fn call(&mut self, _arguments: &[Values<'a>], env: &Environment<'a>) {
+        let Some(frame) = env.frames().last() else {
+            return;
+        };
+        let mut data = self.0.borrow_mut();
+        let now = data.now();
+        //Until its first statement the call runs on the line of the declaration.
+        data.tick(now, frame.function.line());
+        let key = (frame.function.as_str().to_string(), frame.function.line());
+        let next = data.functions.len();
+        let function = *data.index.entry(key).or_insert(next);
+        if function == next {
+            data.functions.push(FunctionProfile {
+                name: frame.function.as_str().to_string(),
+                line: frame.function.line(),
+                ..Default::default()
+            });
+        }
+        data.functions[function].calls += 1;
+        data.calls.push(Call {
+            function,
+            start: now,
+            children: 0.0,
+        });
+    }
+    fn returned(&mut self, _value: Option<&Values<'a>>, env: &Environment<'a>) {
+        let Some(frame) = env.frames().last() else {
+            return;
+        };
+        let mut data = self.0.borrow_mut();
+        let now = data.now();
+        data.tick(now, frame.call_line);
+        let stack: Vec<usize> = data.calls.iter().map(|x| x.function).collect();
+        let Some(call) = data.calls.pop() else {
+            return;
+        };
+        let elapsed = now - call.start;
+        let exclusive = elapsed - call.children;
+        let recursive = data.calls.iter().any(|x| x.function == call.function);
+        let function = &mut data.functions[call.function];
+        function.exclusive += exclusive;
+        if !recursive {
+            function.inclusive += elapsed;
+        }
+        *data.stacks.entry(stack).or_default() += exclusive;
+        match data.calls.last_mut() {
+            Some(x) => x.children += elapsed,
+            None => data.called_from_main += elapsed,
+        }
+    }
+}
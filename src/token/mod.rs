@@ -1,4 +1,4 @@
-use std::{error::Error, fmt::Display, mem::take};
+use std::{error::Error, fmt::Display, mem::take, ops::Range};
 
 use ascii::{AsAsciiStr, AsciiChar, AsciiStr};
 
@@ -82,6 +82,8 @@ impl TokenType {
 pub struct Token<'a> {
     token_type: TokenType,
     lexeme: &'a AsciiStr,
+    ///White space and comments between the previous token and this one.
+    trivia: &'a AsciiStr,
     line: usize,
     coloum: usize,
 }
@@ -90,6 +92,7 @@ impl<'a> Token<'a> {
         Self {
             token_type,
             lexeme,
+            trivia: AsciiStr::from_ascii(b"").unwrap(),
             line,
             coloum,
         }
@@ -98,6 +101,7 @@ impl<'a> Token<'a> {
         Self{
             token_type,
             lexeme:unsafe{"".as_ascii_str_unchecked()},
+            trivia:unsafe{"".as_ascii_str_unchecked()},
             line:0,
             coloum:0,
         }
@@ -122,14 +126,57 @@ impl<'a> Token<'a> {
     pub fn as_str(self) -> &'a str {
         self.lexeme.as_str()
     }
+    ///The white space and `//` comments in front of the token,the end of file token holds the
+    ///ones at the end of the source.
+    pub fn trivia(&self) -> &'a str {
+        self.trivia.as_str()
+    }
+    ///The text of the `///` comments on the lines right above the token,without the slashes and
+    ///one space after them.A blank line or another comment in between ends them.`after_token`
+    ///tells if another token comes before this one,a comment on its line is left out.
+    pub fn doc_comment(&self, after_token: bool) -> Option<String> {
+        let mut lines: Vec<&str> = self.trivia().split('\n').collect();
+        //The token has to start its line.
+        if !lines.pop()?.trim().is_empty() {
+            return None;
+        }
+        if after_token && !lines.is_empty() {
+            lines.remove(0);
+        }
+        let mut output: Vec<&str> = lines
+            .into_iter()
+            .rev()
+            .map(str::trim)
+            .take_while(|x| x.starts_with("///") && !x.starts_with("////"))
+            .map(|x| x[3..].strip_prefix(' ').unwrap_or(&x[3..]))
+            .collect();
+        output.reverse();
+        (!output.is_empty()).then(|| output.join("\n"))
+    }
 
+    ///Line of the token,starting at 1.
+    pub fn line(&self) -> usize {
+        self.line + 1
+    }
+    ///Column of the token on its line,as counted by the scanner.With the line it tells apart the
+    ///tokens of one source.
+    pub fn column(&self) -> usize {
+        self.coloum
+    }
+    ///Byte range of the lexeme in `source`,the code the token was scanned from.
+    ///`None` for tokens made by the parser,like the end of file and error tokens.
+    pub fn range_in(&self, source: &str) -> Option<Range<usize>> {
+        let start = (self.lexeme.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+        let end = start + self.lexeme.len();
+        (end <= source.len() && !self.lexeme.is_empty()).then_some(start..end)
+    }
     pub(crate) fn span(&self) -> String {
         format!("{}:{}", self.line, self.coloum)
     }
 }
 impl Display for Token<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.lexeme.to_string())
+        write!(f, "{}", self.lexeme)
     }
 }
 #[derive(Debug, Clone, Copy)]
@@ -141,7 +188,7 @@ impl TokenizationErrorType {
     fn as_str(&self) -> String {
         match self {
            Self::UnIdentifiedLetter(x)=>format!("Character not identified:{}",x),
-           Self::UnFinishedString=>format!("You didn't end the string."),
+           Self::UnFinishedString=>"You didn't end the string.".to_string(),
         }
     }
 }
@@ -149,8 +196,19 @@ impl TokenizationErrorType {
 pub struct TokenizationError {
     line: usize,
     coloum: usize,
+    start: usize,
+    end: usize,
     error_type: TokenizationErrorType,
 }
+impl TokenizationError {
+    ///Byte range of the source that couldn't be scanned.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+    pub fn message(&self) -> String {
+        self.error_type.as_str()
+    }
+}
 impl Display for TokenizationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -173,6 +231,8 @@ pub struct Scanner<'a> {
     coloum: usize,
     current: usize,
     start: usize,
+    ///Where the trivia of the next token starts,the end of the last token.
+    trivia_start: usize,
     errors: Vec<TokenizationError>,
 }
 type Result<'a>=std::result::Result<Tokens<'a>,TokenizationErrors>;
@@ -183,6 +243,7 @@ impl<'a> Scanner<'a> {
             tokens: Vec::new(),
             current: 0,
             start: 0,
+            trivia_start: 0,
             line: 0,
             coloum: 0,
             errors: Vec::new(),
@@ -191,14 +252,23 @@ impl<'a> Scanner<'a> {
     pub fn scan_tokens(mut self) -> Result<'a> {
         while !self.is_at_end() {
             self.start = self.current;
+            let start = self.start;
+            let count = self.tokens.len();
             self.scan_token();
+            //A string token starts after its quote,so the trivia is cut here instead.
+            if let Some(x) = self.tokens.get_mut(count) {
+                x.trivia = &self.source[self.trivia_start..start];
+                self.trivia_start = self.current;
+            }
         }
-        self.tokens.push(Token::new(
+        let mut eof = Token::new(
             TokenType::Eof,
             AsciiStr::from_ascii(b"").unwrap(),
             self.line,
             self.coloum,
-        ));
+        );
+        eof.trivia = &self.source[self.trivia_start.min(self.source.len())..];
+        self.tokens.push(eof);
         match self.get_error(){
             Some(x)=>Err(x),
             None=>Ok(self.tokens),
@@ -208,6 +278,8 @@ impl<'a> Scanner<'a> {
         let error = TokenizationError {
             line: self.line,
             coloum: self.coloum,
+            start: self.start,
+            end: self.current,
             error_type,
         };
         self.errors.push(error);
@@ -287,9 +359,9 @@ impl<'a> Scanner<'a> {
             '\n' => self.token_line(), // string,literal,number
             '"' => self.token_string(),
             x => {
-                if c.is_digit(10) {
+                if c.is_ascii_digit() {
                     self.token_digit();
-                } else if c.is_ascii_alphabetic() {
+                } else if c.is_ascii_alphabetic() || c == '_' {
                     self.token_identifier();
                 } else {
                     self.throw_error(TokenizationErrorType::UnIdentifiedLetter(x));
@@ -307,12 +379,13 @@ impl<'a> Scanner<'a> {
         }
         if self.is_at_end() {
             self.throw_error(TokenizationErrorType::UnFinishedString);
+            return;
         }
         self.add_token(TokenType::String);
         self.advance();
     }
     fn token_identifier(&mut self) {
-        while self.peek().is_ascii_alphanumeric() {
+        while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
             self.advance();
         }
         self.add_token(TokenType::keyword(
@@ -357,12 +430,12 @@ impl<'a> Scanner<'a> {
         self.source[self.current + 1].into()
     }
     fn token_digit(&mut self) {
-        while self.peek().is_digit(10) {
+        while self.peek().is_ascii_digit() {
             self.advance();
         }
-        if self.peek() == '.' && self.peek_next().is_digit(10) {
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.advance();
-            while self.peek().is_digit(10) {
+            while self.peek().is_ascii_digit() {
                 self.advance();
             }
         }
@@ -0,0 +1,112 @@
+//!`rlox --trace`,a log of what a script does,written as it runs through the statement hook.
+//!
+//!Every statement is logged with its line and the code on it,calls with their arguments,returns
+//!with their value and every variable set with its new value.Lines are indented two spaces for
+//!each call that hasn't returned,a call and its return at the depth of the caller.
+use std::{
+    error::Error,
+    io::{self, Write},
+};
+
+use crate::{
+    interner::Symbol,
+    interpreter::{debug::StatementHook, environment::Environment},
+    lox_object::Values,
+};
+
+///Statement hook writing the trace of a run.
+pub struct Tracer {
+    out: Box<dyn Write>,
+    ///Lines of the code traced,to show the statements.
+    source: Vec<String>,
+    ///Only what runs inside a call of the function with this name is logged.
+    function: Option<String>,
+    ///A failed write,given back before the next statement runs.
+    error: Option<io::Error>,
+}
+impl Tracer {
+    ///Trace the run of `source` to `out`.
+    pub fn new(out: Box<dyn Write>, source: &str) -> Self {
+        Self {
+            out,
+            source: source.lines().map(|x| x.trim().to_string()).collect(),
+            function: None,
+            error: None,
+        }
+    }
+    ///Only log what runs inside the calls of `function`,the calls it makes included.
+    pub fn set_function(&mut self, function: Option<String>) {
+        self.function = function;
+    }
+    fn traced(&self, env: &Environment<'_>) -> bool {
+        match &self.function {
+            Some(name) => env.frames().iter().any(|x| x.function.as_str() == name),
+            None => true,
+        }
+    }
+    fn log(&mut self, env: &Environment<'_>, depth: usize, message: &str) {
+        if self.error.is_some() || !self.traced(env) {
+            return;
+        }
+        let indent = "  ".repeat(depth);
+        if let Err(x) = writeln!(self.out, "{}{}", indent, message) {
+            self.error = Some(x);
+        }
+    }
+}
+impl<'a> StatementHook<'a> for Tracer {
+    fn before_statement(
+        &mut self,
+        line: usize,
+        env: &Environment<'a>,
+    ) -> Result<(), Box<dyn Error>> {
+        let code = line.checked_sub(1).and_then(|x| self.source.get(x));
+        let code = code.cloned().unwrap_or_default();
+        self.log(
+            env,
+            env.frames().len(),
+            &format!("[line {}] {}", line, code),
+        );
+        match self.error.take() {
+            Some(x) => Err(format!("Can't write the trace:{}", x).into()),
+            None => Ok(()),
+        }
+    }
+    fn call(&mut self, arguments: &[Values<'a>], env: &Environment<'a>) {
+        let Some(frame) = env.frames().last() else {
+            return;
+        };
+        let arguments: Vec<String> = arguments.iter().map(Values::repr).collect();
+        let message = format!(
+            "call {}({}) from line {}",
+            frame.function.as_str(),
+            arguments.join(", "),
+            frame.call_line
+        );
+        self.log(env, env.frames().len() - 1, &message);
+    }
+    fn returned(&mut self, value: Option<&Values<'a>>, env: &Environment<'a>) {
+        let Some(frame) = env.frames().last() else {
+            return;
+        };
+        let message = match value {
+            Some(x) => format!("return {} -> {}", frame.function.as_str(), x.repr()),
+            None => format!("return {} with an error", frame.function.as_str()),
+        };
+        self.log(env, env.frames().len() - 1, &message);
+    }
+    fn defined(&mut self, name: Symbol, value: &Values<'a>, env: &Environment<'a>) {
+        self.log(
+            env,
+            env.frames().len(),
+            &format!("define {} = {}", name, value.repr()),
+        );
+    }
+    fn redefined(&mut self, name: Symbol, value: &Values<'a>, env: &Environment<'a>) {
+        self.log(
+            env,
+            env.frames().len(),
+            &format!("assign {} = {}", name, value.repr()),
+        );
+    }
+}
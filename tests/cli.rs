@@ -0,0 +1,11 @@
+//!Runs the `rlox` binary the way a shell would.
+use std::process::Command;
+
+#[test]
+fn no_script_prints_the_usage() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rlox")).output().unwrap();
+    assert_eq!(output.status.code(), Some(64));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("Usage:rlox "), "{}", stderr);
+}
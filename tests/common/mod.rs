@@ -0,0 +1,15 @@
+use rlox::{interpreter::Interpreter, lox_runner::code_to_stblock};
+
+///Run `code` in a fresh interpreter,giving back what it printed or the runtime error.
+pub fn run(code: &str) -> Result<String, String> {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock(code).map_err(|x| x.to_string())?;
+    let mut interpreter = Interpreter::new(&mut stdout);
+    let result = interpreter.try_interpret(&ast);
+    drop(interpreter);
+    drop(ast);
+    match result {
+        Ok(()) => Ok(String::from_utf8(stdout).unwrap()),
+        Err(x) => Err(x.to_string()),
+    }
+}
@@ -0,0 +1,43 @@
+mod common;
+use common::run;
+
+#[test]
+fn every_kind_of_value() {
+    let output = run("
+        fun f() {}
+        var values = list(\"ab\", 1.5, true, nil, f, list(1, \"x\"), len);
+        for (var i = 0; i < len(values); i = i + 1) {
+            var x = get(values, i);
+            print(type(x), str(x), repr(x), bool(x));
+        }");
+    assert_eq!(
+        output.unwrap(),
+        "string ab \"ab\" true \n\
+         number 1.5 1.5 true \n\
+         bool true true true \n\
+         nil nil nil false \n\
+         function A function <fn f> true \n\
+         list [1, x] [1, \"x\"] true \n\
+         function A function <fn len> true \n"
+    );
+}
+
+#[test]
+fn str_and_print_agree_on_nil() {
+    let output = run("print(nil, str(nil) == \"nil\", str(nil) == repr(nil));");
+    assert_eq!(output.unwrap(), "nil true true \n");
+}
+
+#[test]
+fn num_reads_numbers_written_like_literals() {
+    let output = run("print(num(\"12\"), num(\" -3.25 \"), num(\"0.5\") + 1);");
+    assert_eq!(output.unwrap(), "12 -3.25 1.5 \n");
+    for text in ["abc", "1e3", ".5", "1.", "--1", ""] {
+        let error = run(&format!("num(\"{}\");", text)).unwrap_err();
+        assert_eq!(error, format!("num can't read {:?} as a number.", text));
+    }
+    assert_eq!(
+        run("num(1);").unwrap_err(),
+        "num expects a string as argument 1,got number."
+    );
+}
@@ -0,0 +1,61 @@
+use std::fs;
+
+use rlox::{
+    coverage::{write_lcov, write_summary},
+    lox_runner::test_runner::run_tests_with_coverage,
+};
+
+const LIBRARY: &str = "fun sign(x) {
+  if (x < 0) return -1;
+  if (false) return 0;
+  return 1;
+}
+fun never() {
+  return nil;
+}
+fun test_sign() {
+  assert(sign(-3) == -1);
+  assert(sign(5) == 1 or sign(5) == 2);
+  for (var i = 0; i < 2; i = i + 1) {}
+}
+";
+
+#[test]
+fn records_lines_branches_and_functions() {
+    let name = format!("rlox_coverage_{}", std::process::id());
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("sign.lox"), LIBRARY).unwrap();
+    let (summary, files) = run_tests_with_coverage(&dir, &mut Vec::new()).unwrap();
+    assert!(summary.success());
+    let [file] = files.as_slice() else {
+        panic!("{:?}", files);
+    };
+    //The `if (false)` is kept,the optimizer would have removed it.
+    assert_eq!(file.line_counts(), (9, 10));
+    assert_eq!(file.missed_lines(), [(7, 7)]);
+    assert_eq!(file.branch_counts(), (6, 8));
+    assert_eq!(file.function_counts(), (2, 3));
+
+    let mut summary = Vec::new();
+    write_summary(&files, &mut summary).unwrap();
+    let summary = String::from_utf8(summary).unwrap();
+    assert!(summary.contains("lines 9/10 (90.0%),branches 6/8 (75.0%),functions 2/3 (66.7%)"));
+    assert!(summary.contains("missed lines 7\n"), "{}", summary);
+
+    let mut lcov = Vec::new();
+    write_lcov(&files, &mut lcov).unwrap();
+    let lcov = String::from_utf8(lcov).unwrap();
+    for record in [
+        "FNDA:0,never\n",
+        "BRDA:3,0,0,0\nBRDA:3,0,1,1\n",
+        "BRDA:11,0,0,1\nBRDA:11,0,1,0\n",
+        //The loop went round twice and ended once.
+        "BRDA:12,0,0,2\nBRDA:12,0,1,1\n",
+        "DA:7,0\n",
+        "LF:10\nLH:9\nend_of_record\n",
+    ] {
+        assert!(lcov.contains(record), "{} isn't in\n{}", record, lcov);
+    }
+}
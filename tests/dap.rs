@@ -0,0 +1,174 @@
+//!Drives the `rlox-dap` binary over stdio the way an editor would.
+use std::{
+    collections::VecDeque,
+    fs,
+    io::BufReader,
+    process::{ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use rlox::{
+    json::{self, Json},
+    lsp::{read_message, write_message},
+};
+
+const PROGRAM: &str = "fun add(a, b) {\n  var sum = a + b;\n  return sum;\n}\nvar x = 1;\nvar y = add(x, 2);\nprint(y);\n";
+
+struct Client {
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    seq: usize,
+    ///Events read while waiting for a response.
+    events: VecDeque<Json>,
+}
+impl Client {
+    fn receive(&mut self) -> Json {
+        let body = read_message(&mut self.output).unwrap().unwrap();
+        json::parse(&body).unwrap()
+    }
+    fn request(&mut self, command: &str, arguments: Json) -> Json {
+        self.seq += 1;
+        let message = Json::object([
+            ("seq", self.seq.into()),
+            ("type", "request".into()),
+            ("command", command.into()),
+            ("arguments", arguments),
+        ]);
+        write_message(&mut self.input, &message).unwrap();
+        loop {
+            let message = self.receive();
+            if message.get("type").and_then(Json::as_str) == Some("event") {
+                self.events.push_back(message);
+                continue;
+            }
+            assert_eq!(message.get("request_seq"), Some(&self.seq.into()));
+            assert_eq!(message.get("success"), Some(&true.into()), "{}", message);
+            return message.get("body").cloned().unwrap_or(Json::Null);
+        }
+    }
+    fn event(&mut self, name: &str) -> Json {
+        loop {
+            let message = self.events.pop_front().unwrap_or_else(|| self.receive());
+            if message.get("event").and_then(Json::as_str) == Some(name) {
+                return message.get("body").unwrap().clone();
+            }
+        }
+    }
+    ///The reason of the next `stopped` event.
+    fn stopped(&mut self) -> String {
+        let body = self.event("stopped");
+        body.get("reason").unwrap().as_str().unwrap().to_string()
+    }
+    ///`(name,line)` of every frame,the innermost first.
+    fn stack(&mut self) -> Vec<(String, f64)> {
+        let body = self.request("stackTrace", Json::object([("threadId", 1.0.into())]));
+        let frames = body.get("stackFrames").unwrap().as_array().unwrap().iter();
+        let frame = |x: &Json| {
+            let name = x.get("name").unwrap().as_str().unwrap().to_string();
+            (name, x.get("line").unwrap().as_f64().unwrap())
+        };
+        frames.map(frame).collect()
+    }
+    ///`name=value` of the locals of the innermost frame.
+    fn locals(&mut self) -> Vec<String> {
+        let body = self.request("scopes", Json::object([("frameId", 0.0.into())]));
+        let scope = &body.get("scopes").unwrap().as_array().unwrap()[0];
+        assert_eq!(scope.get("name"), Some(&"Locals".into()));
+        let reference = scope.get("variablesReference").unwrap().clone();
+        let body = self.request(
+            "variables",
+            Json::object([("variablesReference", reference)]),
+        );
+        let variables = body.get("variables").unwrap().as_array().unwrap().iter();
+        let variable = |x: &Json| {
+            let field = |name| x.get(name).unwrap().as_str().unwrap().to_string();
+            format!("{}={}", field("name"), field("value"))
+        };
+        variables.map(variable).collect()
+    }
+}
+
+#[test]
+fn scripted_session() {
+    let program = format!("{}/debugged.lox", env!("CARGO_TARGET_TMPDIR"));
+    fs::write(&program, PROGRAM).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rlox-dap"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut client = Client {
+        input: child.stdin.take().unwrap(),
+        output: BufReader::new(child.stdout.take().unwrap()),
+        seq: 0,
+        events: VecDeque::new(),
+    };
+    let capabilities = client.request("initialize", Json::object([("adapterID", "rlox".into())]));
+    assert_eq!(
+        capabilities.get("supportsConfigurationDoneRequest"),
+        Some(&true.into())
+    );
+    client.event("initialized");
+    client.request(
+        "launch",
+        Json::object([("program", program.as_str().into())]),
+    );
+    let breakpoints = Json::Array(vec![
+        Json::object([("line", 2.0.into())]),
+        Json::object([("line", 4.0.into())]),
+    ]);
+    let source = Json::object([("path", program.as_str().into())]);
+    let body = client.request(
+        "setBreakpoints",
+        Json::object([("source", source), ("breakpoints", breakpoints)]),
+    );
+    let verified: Vec<_> = body
+        .get("breakpoints")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| x.get("verified").unwrap().as_bool().unwrap())
+        .collect();
+    //Line 4 only has the closing brace.
+    assert_eq!(verified, [true, false]);
+    client.request("configurationDone", Json::Null);
+
+    assert_eq!(client.stopped(), "breakpoint");
+    assert_eq!(
+        client.stack(),
+        [("add".to_string(), 2.0), ("main".to_string(), 6.0)]
+    );
+    assert_eq!(client.locals(), ["a=1", "b=2"]);
+
+    client.request("next", Json::object([("threadId", 1.0.into())]));
+    assert_eq!(client.stopped(), "step");
+    assert_eq!(client.stack()[0], ("add".to_string(), 3.0));
+    assert_eq!(client.locals(), ["a=1", "b=2", "sum=3"]);
+
+    client.request("stepOut", Json::object([("threadId", 1.0.into())]));
+    assert_eq!(client.stopped(), "step");
+    assert_eq!(client.stack(), [("main".to_string(), 7.0)]);
+    assert_eq!(client.locals(), ["add=<fn add>", "x=1", "y=3"]);
+
+    client.request("continue", Json::object([("threadId", 1.0.into())]));
+    //What the script prints comes in pieces,before the end.
+    let mut output = String::new();
+    let exited = loop {
+        let message = client
+            .events
+            .pop_front()
+            .unwrap_or_else(|| client.receive());
+        let body = message.get("body").unwrap();
+        match message.get("event").and_then(Json::as_str) {
+            Some("output") => output.push_str(body.get("output").unwrap().as_str().unwrap()),
+            Some("exited") => break body.clone(),
+            _ => (),
+        }
+    };
+    assert_eq!(output, "3 \n");
+    assert_eq!(exited.get("exitCode"), Some(&0.0.into()));
+    client.event("terminated");
+    client.request("disconnect", Json::object([]));
+    drop(client);
+    assert_eq!(child.wait().unwrap().code(), Some(0));
+}
@@ -0,0 +1,88 @@
+use rlox::doc::{document, functions, Format, FunctionDoc};
+
+const CODE: &str = "/// Adds two numbers.
+///
+/// Strings are joined <as is>.
+fun add(a, b) {
+  /// Not listed,it is inside another function.
+  fun inner() {}
+  return a + b;
+}
+/// Ends at the blank line.
+
+// A plain comment.
+fun plain() {}
+var x = 1; /// On the line of code.
+  ///Indented.
+  fun last(c) {}
+";
+
+#[test]
+fn reads_doc_comments() {
+    let found = functions(CODE).unwrap();
+    let docs: Vec<_> = found
+        .iter()
+        .map(|x| (x.signature(), x.doc.as_deref(), x.line))
+        .collect();
+    assert_eq!(
+        docs,
+        [
+            (
+                "add(a, b)".to_string(),
+                Some("Adds two numbers.\n\nStrings are joined <as is>."),
+                4
+            ),
+            ("plain()".to_string(), None, 12),
+            ("last(c)".to_string(), Some("Indented."), 15),
+        ]
+    );
+}
+
+#[test]
+fn writes_markdown() {
+    let found = functions(CODE).unwrap();
+    let markdown = document("lib.lox", &found[..2], Format::Markdown);
+    assert_eq!(
+        markdown,
+        "# lib.lox
+
+## `add(a, b)`
+
+Adds two numbers.
+
+Strings are joined <as is>.
+
+Defined at `lib.lox:4`.
+
+## `plain()`
+
+Defined at `lib.lox:12`.
+"
+    );
+}
+
+#[test]
+fn writes_html() {
+    let function = FunctionDoc {
+        name: "add".to_string(),
+        parameters: vec!["a".to_string(), "b".to_string()],
+        doc: Some("One.\n\nTwo <b>.".to_string()),
+        line: 4,
+    };
+    assert_eq!(
+        document("lib.lox", &[function], Format::Html),
+        "<section class=\"lox-doc\">
+<h1>lib.lox</h1>
+<h2><code>add(a, b)</code></h2>
+<p>One.</p>
+<p>Two &lt;b&gt;.</p>
+<p class=\"location\">Defined at <code>lib.lox:4</code>.</p>
+</section>
+"
+    );
+}
+
+#[test]
+fn reports_parser_errors() {
+    assert!(functions("fun broken( {}").is_err());
+}
@@ -0,0 +1,65 @@
+use std::{fs, path::Path};
+
+use ascii::AsAsciiStr;
+use rlox::{formatter::format_code, token::Scanner};
+
+///The type and text of every token,what formatting must not change.
+fn tokens(code: &str) -> Vec<String> {
+    let tokens = Scanner::new(code.as_ascii_str().unwrap())
+        .scan_tokens()
+        .unwrap();
+    let tokens = tokens.iter();
+    tokens
+        .map(|x| format!("{:?} {}", x.get_type(), x))
+        .collect()
+}
+fn comments(code: &str) -> Vec<&str> {
+    let comments = code.lines().filter_map(|x| x.find("//").map(|i| &x[i..]));
+    comments.map(str::trim_end).collect()
+}
+
+#[test]
+fn formats_spacing_braces_and_comments() {
+    let code = "// counts\n\n\nvar a=-1;   // start\nfun f(x,y){\n\n\treturn x-y;}\nif(a<0)print(f(a,-a));else{print(\"no\");}\nfor(var i=0;i<2;i=i+1){}\n";
+    let expected = "// counts\n\nvar a = -1; // start\nfun f(x, y) {\n  return x - y;\n}\nif (a < 0) print(f(a, -a)); else {\n  print(\"no\");\n}\nfor (var i = 0; i < 2; i = i + 1) {}\n";
+    assert_eq!(format_code(code).unwrap(), expected);
+}
+
+#[test]
+fn refuses_invalid_programs() {
+    assert!(format_code("print(1;").is_err());
+}
+
+#[test]
+fn sample_scripts_are_idempotent() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut files = vec![root.join("test.lox"), root.join("tst2.lox")];
+    for dir in fs::read_dir(root.join("tests/lox")).unwrap() {
+        for file in fs::read_dir(dir.unwrap().path()).unwrap() {
+            files.push(file.unwrap().path());
+        }
+    }
+    let mut formatted_any = false;
+    for file in files {
+        let code = fs::read_to_string(&file).unwrap();
+        //The scripts of the error tests don't compile.
+        let Ok(once) = format_code(&code) else {
+            continue;
+        };
+        formatted_any = true;
+        let twice = format_code(&once).unwrap();
+        assert_eq!(once, twice, "{} isn't stable", file.display());
+        assert_eq!(tokens(&code), tokens(&once), "{}", file.display());
+        assert_eq!(comments(&code), comments(&once), "{}", file.display());
+    }
+    assert!(formatted_any);
+}
+
+#[test]
+fn continues_lines_broken_by_comments() {
+    let code = "{\nvar a = 1 // one\n+ 2;\nprint(a, // first\n// alone\na);\n}\nif (true) print(1); // then\nelse print(2);\nfun f() { // empty\n}\n// end\n";
+    let expected = "{\n  var a = 1 // one\n    + 2;\n  print(a, // first\n    // alone\n    a);\n}\nif (true) print(1); // then\nelse print(2);\nfun f() { // empty\n}\n// end\n";
+    let once = format_code(code).unwrap();
+    assert_eq!(once, expected);
+    assert_eq!(format_code(&once).unwrap(), once);
+}
@@ -0,0 +1,50 @@
+use rlox::{interpreter::Interpreter, lox_runner::code_to_stblock};
+
+fn run(code: &str, filesystem: bool) -> Result<String, String> {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock(code).unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.set_filesystem_access(filesystem);
+    let result = interpreter.try_interpret(&ast);
+    drop(interpreter);
+    drop(ast);
+    result.map_err(|x| x.to_string())?;
+    Ok(String::from_utf8(stdout).unwrap())
+}
+
+#[test]
+fn files_round_trip() {
+    let name = format!("rlox_files_round_trip_{}.txt", std::process::id());
+    let path = std::env::temp_dir().join(name);
+    let code = format!(
+        "var p = {:?};
+        write_file(p, \"a\" + chr(10) + \"b\");
+        append_file(p, chr(10) + \"c\");
+        print(read_lines(p), len(read_file(p)));
+        remove_file(p);
+        print(file_exists(p));",
+        path.to_str().unwrap()
+    );
+    assert_eq!(run(&code, true).unwrap(), "[a, b, c] 5 \nfalse \n");
+}
+
+#[test]
+fn errors_instead_of_panics() {
+    let error = run("read_file(\"/no/such/rlox/file\");", true).unwrap_err();
+    assert!(error.starts_with("read_file failed on \"/no/such/rlox/file\":"));
+    let error = run("file_exists(\"Cargo.toml\");", false).unwrap_err();
+    assert_eq!(
+        error,
+        "file_exists can't be used,filesystem access is disabled."
+    );
+    //Forbidden unless the host allows it.
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock("file_exists(\"Cargo.toml\");").unwrap();
+    let error = Interpreter::new(&mut stdout)
+        .try_interpret(&ast)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "file_exists can't be used,filesystem access is disabled."
+    );
+}
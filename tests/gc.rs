@@ -0,0 +1,55 @@
+use rlox::{interpreter::Interpreter, lox_runner::code_to_stblock};
+
+fn run(code: &str) -> (String, usize, usize) {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock(code).unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.try_interpret(&ast).unwrap();
+    let stats = interpreter.stats();
+    drop(interpreter);
+    drop(ast);
+    (
+        String::from_utf8(stdout).unwrap(),
+        stats.bytes_in_use,
+        stats.peak_bytes,
+    )
+}
+
+#[test]
+fn functions_declared_in_a_loop_are_collected() {
+    let (_, in_use, peak) = run("
+        for (var i = 0; i < 20000; i = i + 1) {
+            fun f() { return i; }
+        }");
+    //Without collection every iteration leaks its scope and closure,several megabytes in total.
+    assert!(in_use < 256 * 1024, "{} bytes still in use", in_use);
+    assert!(peak < 256 * 1024, "peak of {} bytes", peak);
+}
+
+#[test]
+fn gc_builtin_reports_freed_objects() {
+    let (output, _, _) = run("
+        fun make() { fun inner() {} }
+        make();
+        print(gc());
+        print(gc());");
+    assert_eq!(output, "3 \n0 \n");
+}
+
+#[test]
+fn running_close_to_the_limit_does_not_collect_every_step() {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock(
+        "var s = repeat(\"a\", 100000);
+        var c = \"ab\";
+        for (var i = 0; i < 20000; i = i + 1) { var t = c + \"x\"; }",
+    )
+    .unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    //A few KB over what stays alive.
+    interpreter.set_memory_limit(Some(104_000));
+    interpreter.try_interpret(&ast).unwrap();
+    let stats = interpreter.stats();
+    //Collecting whenever the limit is reached would be more than a thousand times.
+    assert!(stats.collections < 200, "{} collections", stats.collections);
+}
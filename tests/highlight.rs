@@ -0,0 +1,57 @@
+use rlox::highlight::{highlight, spans, Class, Format, Span};
+
+const CODE: &str = "// add\nfun add(a, b) {\n  return a + b; // sum\n}\nprint(add(1, \"<x>\"));\n";
+
+#[test]
+fn keeps_the_source() {
+    let spans = spans(CODE).unwrap();
+    let text: String = spans.iter().map(|x| x.text).collect();
+    assert_eq!(text, CODE);
+    let classes: Vec<_> = spans
+        .iter()
+        .filter_map(|x| Some((x.class?, x.text)))
+        .take(6)
+        .collect();
+    assert_eq!(
+        classes,
+        [
+            (Class::Comment, "// add"),
+            (Class::Keyword, "fun"),
+            (Class::Identifier, "add"),
+            (Class::Punctuation, "("),
+            (Class::Identifier, "a"),
+            (Class::Punctuation, ","),
+        ]
+    );
+    assert!(spans.contains(&Span {
+        class: Some(Class::String),
+        text: "\"<x>\"",
+    }));
+}
+
+#[test]
+fn writes_html() {
+    let html = highlight("var x = \"<a>\"; // & more", Format::Html).unwrap();
+    assert_eq!(
+        html,
+        "<pre class=\"lox\"><code><span class=\"lox-keyword\">var</span> \
+         <span class=\"lox-identifier\">x</span> <span class=\"lox-operator\">=</span> \
+         <span class=\"lox-string\">&quot;&lt;a&gt;&quot;</span>\
+         <span class=\"lox-punctuation\">;</span> \
+         <span class=\"lox-comment\">// &amp; more</span></code></pre>\n"
+    );
+}
+
+#[test]
+fn writes_ansi() {
+    let ansi = highlight("var x = 1;\n", Format::Ansi).unwrap();
+    assert_eq!(
+        ansi,
+        "\x1b[1;35mvar\x1b[0m x \x1b[36m=\x1b[0m \x1b[33m1\x1b[0m;\n"
+    );
+}
+
+#[test]
+fn reports_scanner_errors() {
+    assert!(highlight("var x = \"open;", Format::Ansi).is_err());
+}
@@ -0,0 +1,44 @@
+use rlox::json::{parse, Json, ParseError};
+
+mod common;
+use common::run;
+
+#[test]
+fn documents_round_trip() {
+    let text = "{\"a\": [1, -2.5e2, true, null], \"b\": \"x\\n\\u03bb\\ud83d\\ude00\"}";
+    let json = parse(text).unwrap();
+    assert_eq!(json.get("b").and_then(Json::as_str), Some("x\nλ😀"));
+    assert_eq!(
+        json.to_string(),
+        "{\"a\":[1,-250,true,null],\"b\":\"x\\nλ😀\"}"
+    );
+    assert_eq!(parse(&json.to_pretty_string()).unwrap(), json);
+}
+
+#[test]
+fn errors_report_line_and_column() {
+    let error = parse("[1,\n  2,,\n]").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 5));
+    for bad in ["01", "[1", "\"a", "nul", "{\"a\" 1}", "1 2", "\"\\ud800\""] {
+        assert!(matches!(parse(bad), Err(ParseError { .. })), "{}", bad);
+    }
+}
+
+#[test]
+fn scripts_encode_and_decode_lists() {
+    let output = run("
+        var v = json_decode(\"[1, [true, null], 2.5]\");
+        print(v, json_encode(v), json_encode(list()));
+        print(json_encode(list(1, list(\"a\")), true));");
+    assert_eq!(
+        output.unwrap(),
+        "[1, [true, nil], 2.5] [1,[true,null],2.5] [] \n[\n  1,\n  [\n    \"a\"\n  ]\n] \n"
+    );
+    let error = run("json_decode(\"[1,\" + chr(10) + \"  {}]\");").unwrap_err();
+    assert_eq!(
+        error,
+        "json_decode failed:Objects are not supported,lox has no map value at line 2,column 3."
+    );
+    let error = run("json_encode(print);").unwrap_err();
+    assert_eq!(error, "json_encode can't encode a function.");
+}
@@ -0,0 +1,119 @@
+use std::{
+    sync::atomic::Ordering,
+    thread,
+    time::{Duration, Instant},
+};
+
+use rlox::{
+    interpreter::{
+        limits::{Interrupt, DEFAULT_MAX_DEPTH},
+        Interpreter,
+    },
+    lox_runner::code_to_stblock,
+};
+
+const LOOP: &str = "while (true) {}";
+const RECURSION: &str = "fun down(n) { var deeper = n + 1; down(deeper); } down(0);";
+const TIMEOUT: Duration = Duration::from_millis(20);
+
+#[derive(Clone, Copy)]
+enum Limit {
+    Steps,
+    Timeout,
+    Cancel,
+}
+
+///Run `code` under `limit` and give back why it stopped,then check the interpreter still runs
+///scripts,in the scope it had before.The call depth isn't limited so the recursion goes on until
+///`limit` stops it,which takes a thread with a big stack.
+fn stop(code: &'static str, limit: Limit) -> Interrupt {
+    let runner = thread::Builder::new().stack_size(512 * 1024 * 1024);
+    let handle = runner.spawn(move || {
+        let mut stdout = Vec::new();
+        let leaked = code_to_stblock("print(deeper);").unwrap();
+        let ast = code_to_stblock(code).unwrap();
+        let after = code_to_stblock("var after = 2; print(after);").unwrap();
+        let mut interpreter = Interpreter::new(&mut stdout);
+        interpreter.set_max_call_depth(None);
+        let cancel = interpreter.cancel_handle();
+        let canceller = match limit {
+            Limit::Steps => {
+                interpreter.set_step_limit(Some(1000));
+                None
+            }
+            Limit::Timeout => {
+                interpreter.set_timeout(Some(TIMEOUT));
+                None
+            }
+            Limit::Cancel => {
+                let cancel = cancel.clone();
+                Some(thread::spawn(move || {
+                    thread::sleep(TIMEOUT);
+                    cancel.store(true, Ordering::Relaxed);
+                }))
+            }
+        };
+        let start = Instant::now();
+        let error = interpreter.try_interpret(&ast).unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(10));
+        if let Some(x) = canceller {
+            x.join().unwrap();
+        }
+        let interrupt = *error.downcast_ref::<Interrupt>().unwrap();
+        interpreter.set_step_limit(None);
+        interpreter.set_timeout(None);
+        cancel.store(false, Ordering::Relaxed);
+        interpreter.try_interpret(&after).unwrap();
+        //The scope of the function that was stopped is gone.
+        let error = interpreter.try_interpret(&leaked).unwrap_err();
+        assert_eq!(error.to_string(), "deeper Variable not declared.");
+        drop(interpreter);
+        drop((leaked, ast, after));
+        assert_eq!(String::from_utf8(stdout).unwrap(), "2 \n");
+        interrupt
+    });
+    handle.unwrap().join().unwrap()
+}
+
+#[test]
+fn a_step_limit_stops_loops_and_recursion() {
+    assert_eq!(stop(LOOP, Limit::Steps), Interrupt::StepLimit(1000));
+    assert_eq!(stop(RECURSION, Limit::Steps), Interrupt::StepLimit(1000));
+}
+
+#[test]
+fn a_timeout_stops_loops_and_recursion() {
+    assert_eq!(stop(LOOP, Limit::Timeout), Interrupt::Timeout(TIMEOUT));
+    assert_eq!(stop(RECURSION, Limit::Timeout), Interrupt::Timeout(TIMEOUT));
+}
+
+#[test]
+fn cancelling_stops_loops_and_recursion() {
+    assert_eq!(stop(LOOP, Limit::Cancel), Interrupt::Cancelled);
+    assert_eq!(stop(RECURSION, Limit::Cancel), Interrupt::Cancelled);
+}
+
+#[test]
+fn deep_recursion_stops_before_the_stack_runs_out() {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock("fun f(n) { return f(n + 1); } f(0);").unwrap();
+    let after =
+        code_to_stblock("fun g(n) { if (n > 0) return g(n - 1); return 7; } print(g(100));")
+            .unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    let error = interpreter.try_interpret(&ast).unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<Interrupt>(),
+        Some(&Interrupt::CallDepth(DEFAULT_MAX_DEPTH))
+    );
+    interpreter.try_interpret(&after).unwrap();
+    interpreter.set_max_call_depth(Some(10));
+    let error = interpreter.try_interpret(&after).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Execution stopped:more than 10 nested calls."
+    );
+    drop(interpreter);
+    drop((ast, after));
+    assert_eq!(String::from_utf8(stdout).unwrap(), "7 \n");
+}
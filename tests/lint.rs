@@ -0,0 +1,57 @@
+use rlox::lint::{lint, Code};
+
+fn codes(code: &str) -> Vec<(usize, Code)> {
+    let warnings = lint(code).unwrap();
+    warnings.into_iter().map(|x| (x.line, x.code)).collect()
+}
+
+#[test]
+fn finds_each_kind_of_warning() {
+    let code = "var g = 1;
+fun f(a, b) {
+  var g = 2;
+  g = g;
+  return a;
+  print(g);
+}
+print(f(1 == \"1\", 2), len(\"a\", \"b\"));
+";
+    assert_eq!(
+        codes(code),
+        [
+            (2, Code::UnusedVariable),
+            (3, Code::Shadowing),
+            (4, Code::SelfAssignment),
+            (6, Code::Unreachable),
+            (8, Code::MixedComparison),
+            (8, Code::BuiltinArity),
+        ]
+    );
+}
+
+#[test]
+fn clean_code_has_no_warnings() {
+    let code = "fun count(_unused) {
+  var n = 0;
+  for (var i = 0; i < 3; i = i + 1) return i;
+  return n;
+}
+fun len(x) { return x; }
+print(count(1), len(1, 2));
+";
+    assert_eq!(codes(code), []);
+}
+
+#[test]
+fn comments_silence_warnings() {
+    let code = "// lint: allow-file self-assignment
+fun f() {
+  var a = 1; // lint: allow L001
+  // lint: allow L001, L004
+  var b = 2;
+  a = a;
+}
+f();
+";
+    assert_eq!(codes(code), []);
+}
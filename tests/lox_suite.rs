@@ -0,0 +1,105 @@
+//!Runs every `.lox` file under `tests/lox/` and checks it against the comments inside it,in the
+//!format of the Crafting Interpreters test suite:
+//!
+//!- `// expect: text` is the next line the script prints.
+//!- `// expect runtime error: message` is the error the run ends with.
+//!- `// Error ...` or `// [line N] Error ...` means the script must not compile.The message of the
+//!  suite is kept for the reader but not compared,the parser words its errors differently.
+//!
+//!`print` writes a space after every value,so trailing white space of a line is ignored.
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+use rlox::{interpreter::Interpreter, lox_runner::code_to_stblock};
+
+#[derive(Debug, Default, PartialEq)]
+struct Expectations {
+    output: Vec<String>,
+    runtime_error: Option<String>,
+    compile_error: bool,
+}
+impl Expectations {
+    fn read(code: &str) -> Self {
+        let mut output = Self::default();
+        for line in code.lines() {
+            let Some((_, comment)) = line.split_once("//") else {
+                continue;
+            };
+            let comment = comment.trim_start();
+            if let Some(x) = comment.strip_prefix("expect: ") {
+                output.output.push(x.trim_end().to_string());
+            } else if let Some(x) = comment.strip_prefix("expect runtime error: ") {
+                output.runtime_error = Some(x.trim_end().to_string());
+            } else if comment.starts_with("Error") || comment.starts_with("[line ") {
+                output.compile_error = true;
+            }
+        }
+        output
+    }
+}
+
+///What a script actually did.
+fn run(code: &str) -> Expectations {
+    let mut stdout = Vec::new();
+    let mut result = Expectations::default();
+    match code_to_stblock(code) {
+        Err(_) => result.compile_error = true,
+        Ok(ast) => {
+            let mut interpreter = Interpreter::new(&mut stdout);
+            if let Err(x) = interpreter.try_interpret(&ast) {
+                result.runtime_error = Some(x.to_string());
+            }
+        }
+    }
+    let stdout = String::from_utf8_lossy(&stdout);
+    result.output = stdout.lines().map(|x| x.trim_end().to_string()).collect();
+    result
+}
+
+fn lox_files(dir: &Path, output: &mut Vec<PathBuf>) {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|x| x.unwrap().path())
+        .collect();
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            lox_files(&entry, output);
+        } else if entry.extension().is_some_and(|x| x == "lox") {
+            output.push(entry);
+        }
+    }
+}
+
+#[test]
+fn lox_scripts_match_their_expectations() {
+    let mut files = Vec::new();
+    lox_files(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lox"), &mut files);
+    assert!(!files.is_empty(), "no scripts found in tests/lox");
+    let mut failures = Vec::new();
+    for file in &files {
+        let code = fs::read_to_string(file).unwrap();
+        let expected = Expectations::read(&code);
+        //One script panicking shouldn't hide the results of the others.
+        match panic::catch_unwind(AssertUnwindSafe(|| run(&code))) {
+            Ok(actual) if actual == expected => (),
+            Ok(actual) => failures.push(format!(
+                "{}\n  expected:{:?}\n  got:     {:?}",
+                file.display(),
+                expected,
+                actual
+            )),
+            Err(_) => failures.push(format!("{}\n  the interpreter panicked", file.display())),
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} scripts failed:\n{}",
+        failures.len(),
+        files.len(),
+        failures.join("\n")
+    );
+}
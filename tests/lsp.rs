@@ -0,0 +1,146 @@
+//!Drives the `rlox-lsp` binary over stdio the way an editor would.
+use std::{
+    io::{BufReader, Cursor, ErrorKind, Write},
+    process::{ChildStdout, Command, Stdio},
+};
+
+use rlox::{
+    json::{self, Json},
+    lsp::{read_message, write_message},
+};
+
+const URI: &str = "file:///test.lox";
+
+struct Client {
+    input: std::process::ChildStdin,
+    output: BufReader<ChildStdout>,
+    id: usize,
+}
+impl Client {
+    fn send(&mut self, method: &str, params: Json, id: Option<usize>) {
+        let mut message = vec![("jsonrpc", "2.0".into()), ("method", method.into())];
+        if let Some(id) = id {
+            message.push(("id", id.into()));
+        }
+        message.push(("params", params));
+        write_message(&mut self.input, &Json::object(message)).unwrap();
+    }
+    fn receive(&mut self) -> Json {
+        let body = read_message(&mut self.output).unwrap().unwrap();
+        json::parse(&body).unwrap()
+    }
+    fn request(&mut self, method: &str, params: Json) -> Json {
+        self.id += 1;
+        self.send(method, params, Some(self.id));
+        let response = self.receive();
+        assert_eq!(response.get("id"), Some(&self.id.into()));
+        response.get("result").unwrap().clone()
+    }
+    fn at(&mut self, method: &str, line: usize, character: usize) -> Json {
+        let position = Json::object([("line", line.into()), ("character", character.into())]);
+        let params = Json::object([
+            ("textDocument", Json::object([("uri", URI.into())])),
+            ("position", position),
+        ]);
+        self.request(method, params)
+    }
+}
+
+///`(line,character)` of the start of every range in `locations`.
+fn starts(locations: &Json) -> Vec<(f64, f64)> {
+    let start = |x: &Json| {
+        let start = x.get("range").unwrap().get("start").unwrap();
+        let field = |name| start.get(name).unwrap().as_f64().unwrap();
+        (field("line"), field("character"))
+    };
+    locations.as_array().unwrap().iter().map(start).collect()
+}
+
+#[test]
+fn scripted_session() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rlox-lsp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut client = Client {
+        input: child.stdin.take().unwrap(),
+        output: BufReader::new(child.stdout.take().unwrap()),
+        id: 0,
+    };
+    let capabilities = client.request("initialize", Json::object([]));
+    let capabilities = capabilities.get("capabilities").unwrap();
+    assert_eq!(capabilities.get("hoverProvider"), Some(&true.into()));
+    client.send("initialized", Json::object([]), None);
+
+    let document = Json::object([
+        ("uri", URI.into()),
+        ("languageId", "lox".into()),
+        ("version", 1.0.into()),
+        ("text", "var a = 1\nprint(a);".into()),
+    ]);
+    client.send(
+        "textDocument/didOpen",
+        Json::object([("textDocument", document)]),
+        None,
+    );
+    let published = client.receive();
+    let diagnostics = published.get("params").unwrap().get("diagnostics").unwrap();
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+
+    let text = "fun add(x, y) {\n  var sum = x + y;\n  return sum;\n}\nvar total = add(1, 2);\nprint(add(total, 3));\n";
+    let change = Json::object([
+        ("textDocument", Json::object([("uri", URI.into())])),
+        (
+            "contentChanges",
+            Json::Array(vec![Json::object([("text", text.into())])]),
+        ),
+    ]);
+    client.send("textDocument/didChange", change, None);
+    let published = client.receive();
+    let diagnostics = published.get("params").unwrap().get("diagnostics").unwrap();
+    assert_eq!(diagnostics.as_array().unwrap().len(), 0);
+
+    //`sum` in `return sum;` goes to `var sum`.
+    let definition = client.at("textDocument/definition", 2, 10);
+    assert_eq!(starts(&Json::Array(vec![definition])), vec![(1.0, 6.0)]);
+
+    let references = client.at("textDocument/references", 0, 5);
+    assert_eq!(
+        starts(&references),
+        vec![(0.0, 4.0), (4.0, 12.0), (5.0, 6.0)]
+    );
+
+    let hover = client.at("textDocument/hover", 5, 7);
+    let value = hover.get("contents").unwrap().get("value").unwrap();
+    assert_eq!(value.as_str(), Some("```lox\nfun add(x, y)\n```"));
+
+    let params = Json::object([("textDocument", Json::object([("uri", URI.into())]))]);
+    let symbols = client.request("textDocument/documentSymbol", params);
+    let names = |x: &Json| -> Vec<String> {
+        let symbols = x.as_array().unwrap().iter();
+        symbols
+            .map(|x| x.get("name").unwrap().as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(names(&symbols), ["add", "total"]);
+    let children = symbols.as_array().unwrap()[0].get("children").unwrap();
+    assert_eq!(names(children), ["sum"]);
+
+    client.request("shutdown", Json::Null);
+    client.send("exit", Json::Null, None);
+    client.input.flush().unwrap();
+    assert_eq!(child.wait().unwrap().code(), Some(0));
+}
+
+#[test]
+fn refuses_huge_and_cut_messages() {
+    let mut input = Cursor::new("Content-Length: 99999999999\r\n\r\n{}");
+    let error = read_message(&mut input).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    let mut input = Cursor::new("Content-Length: 10\r\n\r\n{}");
+    let error = read_message(&mut input).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    let mut input = Cursor::new("Content-Length: 2\r\n\r\n{}");
+    assert_eq!(read_message(&mut input).unwrap().as_deref(), Some("{}"));
+}
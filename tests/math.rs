@@ -0,0 +1,57 @@
+mod common;
+use common::run;
+
+#[test]
+fn representative_results() {
+    let output = run("
+        print(floor(2.7), floor(-2.5), ceil(2.1), round(2.5), abs(-3));
+        print(sqrt(16), sqrt(-1), pow(2, 10), pow(4, 0.5));
+        print(min(3, 1, 2), max(3, 1, 2), min(5), max(-1, -7));
+        print(idiv(7, 2), idiv(-7, 2), mod(7, 3), mod(-7, 3), mod(7, -3));
+        print(is_nan(sqrt(-1)), is_finite(pow(10, 400)), floor(PI), floor(E));");
+    assert_eq!(
+        output.unwrap(),
+        "2 -3 3 3 3 \n4 NaN 1024 2 \n1 3 5 -1 \n3 -4 1 2 -2 \ntrue false 3 2 \n"
+    );
+}
+
+#[test]
+fn arity_errors() {
+    assert_eq!(
+        run("sqrt(1, 2);").unwrap_err(),
+        "Function sqrt have different arguments."
+    );
+    assert_eq!(
+        run("pow(2);").unwrap_err(),
+        "Function pow have different arguments."
+    );
+    assert_eq!(
+        run("min();").unwrap_err(),
+        "Function min have different arguments."
+    );
+}
+
+#[test]
+fn type_errors_name_the_function() {
+    assert_eq!(
+        run("floor(\"a\");").unwrap_err(),
+        "floor expects a number as argument 1,got string."
+    );
+    assert_eq!(
+        run("max(1, nil);").unwrap_err(),
+        "max expects a number as argument 2,got nil."
+    );
+    assert_eq!(
+        run("pow(2, true);").unwrap_err(),
+        "pow expects a number as argument 2,got bool."
+    );
+}
+
+#[test]
+fn a_zero_divisor_is_an_error() {
+    assert_eq!(
+        run("idiv(1, 0);").unwrap_err(),
+        "idiv got division by zero."
+    );
+    assert_eq!(run("mod(1, 0);").unwrap_err(), "mod got division by zero.");
+}
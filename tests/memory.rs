@@ -0,0 +1,47 @@
+use rlox::{
+    interpreter::{limits::Interrupt, Interpreter},
+    lox_runner::code_to_stblock,
+};
+
+#[test]
+fn a_memory_limit_stops_a_growing_string() {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock(
+        "var text = \"\";
+        while (true) { text = text + \"0123456789\"; }",
+    )
+    .unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.set_memory_limit(Some(64 * 1024));
+    let error = interpreter.try_interpret(&ast).unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<Interrupt>(),
+        Some(&Interrupt::OutOfMemory(64 * 1024))
+    );
+    //What the script held is gone with its scope.
+    let stats = interpreter.stats();
+    assert!(stats.bytes_in_use < 16 * 1024, "{:?}", stats);
+    assert!(stats.peak_bytes > 64 * 1024, "{:?}", stats);
+}
+
+#[test]
+fn peak_stays_when_values_are_dropped() {
+    let mut stdout = Vec::new();
+    let big =
+        code_to_stblock("var text = repeat(\"0123456789\", 20000); print(len(text));").unwrap();
+    let small = code_to_stblock("var text = \"small\";").unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    let before = interpreter.stats();
+    interpreter.try_interpret(&big).unwrap();
+    let after_big = interpreter.stats();
+    assert!(after_big.peak_bytes >= 200_000, "{:?}", after_big);
+    assert!(
+        after_big.bytes_in_use < before.bytes_in_use + 16 * 1024,
+        "{:?}",
+        after_big
+    );
+    interpreter.try_interpret(&small).unwrap();
+    let after_small = interpreter.stats();
+    assert_eq!(after_small.peak_bytes, after_big.peak_bytes);
+    assert!(after_small.bytes_in_use < 200_000, "{:?}", after_small);
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "nan-boxing")]
+use std::rc::Rc;
+
+use rlox::lox_object::{builtinfunction::NativeFunc, nanbox::NanBox, LoxCallable, Values};
+
+fn round_trip(value: Values) -> Values {
+    let boxed = NanBox::from(value);
+    let copy = boxed.clone();
+    drop(boxed);
+    copy.to_value()
+}
+
+#[test]
+fn numbers_keep_their_bits() {
+    for x in [
+        0.0,
+        -0.0,
+        1.5,
+        -2.25,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::MAX,
+    ] {
+        let Values::Number(y) = round_trip(Values::Number(x)) else {
+            panic!("{} came back as another kind", x);
+        };
+        assert_eq!(y.to_bits(), x.to_bits());
+    }
+    for x in [f64::NAN, -f64::NAN, f64::from_bits(0x7ffc_0000_0000_0001)] {
+        let Values::Number(y) = round_trip(Values::Number(x)) else {
+            panic!("NaN came back as another kind");
+        };
+        assert!(y.is_nan());
+    }
+}
+
+#[test]
+fn null_and_booleans_come_back() {
+    assert!(matches!(round_trip(Values::Null), Values::Null));
+    assert!(matches!(round_trip(false.into()), Values::Boolean(x) if x == 0.0));
+    assert!(matches!(round_trip(true.into()), Values::Boolean(x) if x == 1.0));
+}
+
+#[test]
+fn objects_are_shared_and_freed() {
+    let string: Rc<str> = "text".into();
+    let list: Rc<[Values]> = vec![Values::Number(1.0), Values::Str(string.clone())].into();
+    let function: Rc<dyn LoxCallable> =
+        Rc::new(NativeFunc::new("native", 0..=0, |_, _| Ok(Values::Null)));
+
+    let boxed = NanBox::from(Values::Str(string.clone()));
+    let copies = [boxed.clone(), boxed.clone()];
+    assert_eq!(Rc::strong_count(&string), 3);
+    assert!(matches!(copies[1].to_value(), Values::Str(x) if Rc::ptr_eq(&x, &string)));
+    drop((boxed, copies));
+    assert_eq!(Rc::strong_count(&string), 2);
+
+    let boxed = NanBox::from(Values::List(list.clone()));
+    let copy = boxed.clone();
+    drop(boxed);
+    assert!(matches!(copy.to_value(), Values::List(x) if Rc::ptr_eq(&x, &list)));
+    drop(copy);
+    assert_eq!(Rc::strong_count(&list), 1);
+
+    //A function is counted once for every copy,the garbage collector relies on it.
+    let boxed = NanBox::from(Values::Fn(function.clone()));
+    let copies = [boxed.clone(), boxed.clone()];
+    assert_eq!(Rc::strong_count(&function), 4);
+    drop(boxed);
+    assert_eq!(Rc::strong_count(&function), 3);
+    assert!(matches!(copies[0].to_value(), Values::Fn(x) if Rc::ptr_eq(&x, &function)));
+    drop(copies);
+    assert_eq!(Rc::strong_count(&function), 1);
+}
@@ -0,0 +1,53 @@
+use rlox::{
+    ast::{expression::ExprView, statement::StmtView},
+    interpreter::Interpreter,
+    lox_runner::code_to_stblock_with,
+};
+
+fn run(code: &str, optimize: bool) -> Result<String, String> {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock_with(code, optimize).map_err(|x| x.to_string())?;
+    let mut interpreter = Interpreter::new(&mut stdout);
+    let result = interpreter.try_interpret(&ast);
+    drop(interpreter);
+    drop(ast);
+    result.map_err(|x| x.to_string())?;
+    Ok(String::from_utf8(stdout).unwrap())
+}
+///The initializer of `var x = ...;`,as a value if it was folded.
+fn initializer(code: &str, optimize: bool) -> Option<String> {
+    let ast = code_to_stblock_with(code, optimize).unwrap();
+    let StmtView::Var { initializer, .. } = ast.statements()[0].view() else {
+        panic!("not a var declaration");
+    };
+    match initializer.view() {
+        ExprView::Value(x) => Some(x.to_string()),
+        _ => None,
+    }
+}
+
+#[test]
+fn folds_constant_expressions() {
+    assert_eq!(initializer("var x = (1 + 2) * -3;", true), Some("-9".into()));
+    assert_eq!(initializer("var x = \"a\" + \"b\";", true), Some("ab".into()));
+    assert_eq!(initializer("var x = !(1 < 2) or 4 == 4;", true), Some("true".into()));
+    assert_eq!(initializer("var x = (1 > 2) and y;", true), Some("false".into()));
+    assert_eq!(initializer("var x = 1 + y;", true), None);
+    assert_eq!(initializer("var x = 1 + 2;", false), None);
+}
+
+#[test]
+fn keeps_runtime_errors() {
+    assert!(run("print(1 / 0);", true).is_err());
+    assert_eq!(run("print(1 / 0);", true), run("print(1 / 0);", false));
+    assert_eq!(run("print(-\"a\");", true), run("print(-\"a\");", false));
+}
+
+#[test]
+fn prunes_constant_branches() {
+    let code = "if (1 > 2) print(missing); else print(\"else\");\nwhile (false) print(missing);\nfun f() { if (true) return 1; return missing; }\nprint(f());";
+    assert_eq!(run(code, true), Ok("else \n1 \n".into()));
+    assert_eq!(run(code, true), run(code, false));
+    let ast = code_to_stblock_with("if (false) print(1);", true).unwrap();
+    assert!(matches!(ast.statements()[0].view(), StmtView::Statements([])));
+}
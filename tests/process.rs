@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use rlox::{
+    interpreter::{process::Exit, Interpreter},
+    lox_runner::code_to_stblock,
+};
+
+#[test]
+fn host_sets_args_and_variables() {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock("print(args(), getenv(\"MODE\"), getenv(\"HOME\"));").unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.set_args(vec!["-v".to_string(), "x".to_string()]);
+    let vars = HashMap::from([("MODE".to_string(), "test".to_string())]);
+    interpreter.set_env_vars(Some(vars));
+    interpreter.try_interpret(&ast).unwrap();
+    drop(interpreter);
+    drop(ast);
+    assert_eq!(String::from_utf8(stdout).unwrap(), "[-v, x] test nil \n");
+}
+
+#[test]
+fn real_variables_are_hidden_by_default() {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock("print(getenv(\"PATH\"));").unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.try_interpret(&ast).unwrap();
+    interpreter.set_env_vars(None);
+    interpreter.try_interpret(&ast).unwrap();
+    drop(interpreter);
+    drop(ast);
+    let output = String::from_utf8(stdout).unwrap();
+    let path = std::env::var("PATH").unwrap();
+    assert_eq!(output, format!("nil \n{} \n", path));
+}
+
+#[test]
+fn exit_unwinds_out_of_functions() {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock(
+        "fun down(n) { print(n); if (n == 2) { exit(4); } down(n + 1); }
+        down(0);
+        print(\"unreachable\");",
+    )
+    .unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    let error = interpreter.try_interpret(&ast).unwrap_err();
+    assert_eq!(error.downcast_ref::<Exit>(), Some(&Exit(4)));
+    drop(interpreter);
+    drop(ast);
+    assert_eq!(String::from_utf8(stdout).unwrap(), "0 \n1 \n2 \n");
+}
@@ -0,0 +1,98 @@
+use rlox::{
+    interpreter::{clock::ManualClock, Interpreter},
+    lox_runner::code_to_stblock,
+    profiler::Profiler,
+};
+
+///Seconds of one `sleep(1)` in the scripts below.
+const UNIT: f64 = 0.05;
+
+fn profile(code: &str, clock: Option<ManualClock>) -> Profiler {
+    let profiler = Profiler::new();
+    let mut stdout = Vec::new();
+    let code = code.replace("sleep(1)", &format!("sleep({})", UNIT));
+    let code = code.replace("sleep(2)", &format!("sleep({})", 2.0 * UNIT));
+    let ast = code_to_stblock(&code).unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    if let Some(x) = clock {
+        interpreter.set_clock(Box::new(x));
+    }
+    interpreter.set_statement_hook(Some(Box::new(profiler.clone())));
+    interpreter.try_interpret(&ast).unwrap();
+    profiler
+}
+///Whether `time` is about `units` sleeps,a sleep never ends early but may end late.
+fn about(time: f64, units: f64) -> bool {
+    time >= units * UNIT && time < (units + 0.8) * UNIT
+}
+
+#[test]
+fn times_functions_and_lines() {
+    let profiler = profile(
+        "fun slow() { sleep(2); }
+        fun outer() { slow(); sleep(1); }
+        outer();
+        outer();
+        slow();",
+        None,
+    );
+    assert!(about(profiler.total(), 8.0), "{}", profiler.total());
+    let functions = profiler.functions();
+    let counts: Vec<_> = functions
+        .iter()
+        .map(|x| (x.name.as_str(), x.line, x.calls))
+        .collect();
+    assert_eq!(counts, [("slow", 1, 3), ("outer", 2, 2), ("main", 0, 1)]);
+    let times: Vec<_> = functions
+        .iter()
+        .map(|x| (x.inclusive, x.exclusive))
+        .collect();
+    for ((inclusive, exclusive), expected) in times.into_iter().zip([(6.0, 6.0), (6.0, 2.0)]) {
+        assert!(about(inclusive, expected.0), "{}", inclusive);
+        assert!(about(exclusive, expected.1), "{}", exclusive);
+    }
+    assert!(functions[2].exclusive < 0.8 * UNIT);
+    let hottest = &profiler.lines()[0];
+    //The declaration of `slow` and its three calls.
+    assert_eq!((hottest.line, hottest.count), (1, 4));
+    assert!(about(hottest.time, 6.0));
+
+    let mut folded = Vec::new();
+    profiler.write_folded(&mut folded).unwrap();
+    let folded = String::from_utf8(folded).unwrap();
+    let stacks: Vec<_> = folded
+        .lines()
+        .map(|x| x.rsplit_once(' ').unwrap())
+        .map(|(stack, micros)| (stack, micros.parse::<f64>().unwrap() / 1e6))
+        .collect();
+    let names: Vec<_> = stacks.iter().map(|x| x.0).collect();
+    assert_eq!(
+        names,
+        ["main", "main;outer:2", "main;outer:2;slow:1", "main;slow:1"]
+    );
+    for ((_, time), units) in stacks.iter().skip(1).zip([2.0, 4.0, 2.0]) {
+        assert!(about(*time, units), "{}", folded);
+    }
+}
+
+#[test]
+fn recursion_is_counted_once_in_inclusive_time() {
+    let profiler = profile("fun f(n) { sleep(1); if (n > 0) f(n - 1); }\nf(2);", None);
+    let f = &profiler.functions()[0];
+    assert_eq!((f.name.as_str(), f.calls), ("f", 3));
+    assert!(about(f.inclusive, 3.0), "{}", f.inclusive);
+    assert!(about(f.exclusive, 3.0), "{}", f.exclusive);
+}
+
+#[test]
+fn the_clock_of_the_script_is_not_used() {
+    //The script thinks it slept for 8 units,no time passed.
+    let profiler = profile(
+        "fun slow() { sleep(2); }\nslow();\nslow();\nslow();\nsleep(2);",
+        Some(ManualClock::new(0.0)),
+    );
+    assert!(profiler.total() < UNIT, "{}", profiler.total());
+    let functions = profiler.functions();
+    let slow = functions.iter().find(|x| x.name == "slow").unwrap();
+    assert_eq!(slow.calls, 3);
+}
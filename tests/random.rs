@@ -0,0 +1,66 @@
+use rlox::{interpreter::Interpreter, lox_runner::code_to_stblock};
+
+const ROLLS: &str =
+    "print(random_int(1, 6), random_int(1, 6), random_int(1, 6), shuffle(list(1, 2, 3, 4)));";
+
+fn rolls(interpreter: &mut Interpreter) {
+    let ast = code_to_stblock(ROLLS).unwrap();
+    interpreter.try_interpret(&ast).unwrap();
+}
+
+#[test]
+fn a_seed_gives_a_fixed_sequence() {
+    let mut stdout = Vec::new();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.set_seed(7);
+    rolls(&mut interpreter);
+    drop(interpreter);
+    //Pinned,so a change to the generator that breaks old seeds is noticed.
+    assert_eq!(String::from_utf8(stdout).unwrap(), "4 1 1 [1, 3, 2, 4] \n");
+}
+
+#[test]
+fn interpreters_have_their_own_generator() {
+    let (mut first, mut second) = (Vec::new(), Vec::new());
+    let mut a = Interpreter::new(&mut first);
+    let mut b = Interpreter::new(&mut second);
+    a.set_seed(99);
+    b.set_seed(99);
+    rolls(&mut a);
+    rolls(&mut a);
+    rolls(&mut b);
+    rolls(&mut b);
+    drop(a);
+    drop(b);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn seeding_from_the_script_matches_the_host() {
+    let (mut from_script, mut from_host) = (Vec::new(), Vec::new());
+    let mut script = Interpreter::new(&mut from_script);
+    let seeded = code_to_stblock("seed(7); print(random(), random());").unwrap();
+    script.try_interpret(&seeded).unwrap();
+    script.try_interpret(&seeded).unwrap();
+    let mut host = Interpreter::new(&mut from_host);
+    host.set_seed(7);
+    let unseeded = code_to_stblock("print(random(), random());").unwrap();
+    host.try_interpret(&unseeded).unwrap();
+    drop((script, host, seeded, unseeded));
+    let from_script = String::from_utf8(from_script).unwrap();
+    let (first, second) = from_script.split_at(from_script.len() / 2);
+    assert_eq!(first, second);
+    assert_eq!(first, String::from_utf8(from_host).unwrap());
+}
+
+#[test]
+fn seeds_are_whole_numbers() {
+    let mut stdout = Vec::new();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    let ast = code_to_stblock("seed(1.5);").unwrap();
+    let error = interpreter.try_interpret(&ast).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "seed expects a whole number as argument 1,got 1.5."
+    );
+}
@@ -0,0 +1,76 @@
+mod common;
+use common::run;
+use rlox::{interpreter::Interpreter, lox_runner::code_to_stblock};
+
+#[test]
+fn positions_count_characters_not_bytes() {
+    let output = run("
+        var s = \"a\" + chr(955) + \"b\";
+        print(len(s), char_at(s, 1), substr(s, 1, 3), index_of(s, \"b\"), ord(char_at(s, 1)));
+        print(upper(s), join(split(s, \"\"), \"-\"));");
+    assert_eq!(output.unwrap(), "3 λ λb 2 955 \nAΛB a-λ-b \n");
+}
+
+#[test]
+fn positions_outside_the_string_are_errors() {
+    let error = run("substr(\"a\" + chr(955), 1, 3);").unwrap_err();
+    assert_eq!(
+        error,
+        "substr index 3 is out of range for a string of length 2."
+    );
+    let error = run("char_at(\"abc\", 1.5);").unwrap_err();
+    assert_eq!(
+        error,
+        "char_at expects a whole number that is not negative as argument 2,got 1.5."
+    );
+}
+
+#[test]
+fn split_and_join() {
+    let output = run("
+        var parts = split(\"a,b,,c\", \",\");
+        print(len(parts), get(parts, 1), join(parts, \";\"));");
+    assert_eq!(output.unwrap(), "4 b a;b;;c \n");
+}
+
+#[test]
+fn search_case_and_replace() {
+    let output = run("
+        print(contains(\"hello\", \"ell\"), starts_with(\"hello\", \"he\"), ends_with(\"hello\", \"x\"));
+        print(index_of(\"hello\", \"z\"), lower(\"HeLLo\"), trim(\"  hi \") + \"|\");
+        print(replace(\"a-b-c\", \"-\", \"+\"), repeat(\"ab\", 3));");
+    assert_eq!(
+        output.unwrap(),
+        "true true false \n-1 hello hi| \na+b+c ababab \n"
+    );
+}
+
+#[test]
+fn wrong_types_name_the_function() {
+    let error = run("upper(1);").unwrap_err();
+    assert_eq!(error, "upper expects a string as argument 1,got number.");
+}
+
+#[test]
+fn repeat_rejects_strings_too_long_to_make() {
+    let error = run("repeat(\"ab\", 100000000000000000000);").unwrap_err();
+    assert_eq!(
+        error,
+        "repeat can't repeat a string 100000000000000000000 times,it is too long."
+    );
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock("repeat(\"ab\", 1000000);").unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.set_memory_limit(Some(100_000));
+    let error = interpreter.try_interpret(&ast).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "repeat would make a string of 2000000 bytes,over the memory limit."
+    );
+    //A string that fits is counted once.
+    let fits = code_to_stblock("var s = repeat(\"ab\", 30000); print(len(s));").unwrap();
+    interpreter.try_interpret(&fits).unwrap();
+    drop(interpreter);
+    drop((ast, fits));
+    assert_eq!(String::from_utf8(stdout).unwrap(), "60000 \n");
+}
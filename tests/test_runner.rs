@@ -0,0 +1,44 @@
+use std::fs;
+
+use rlox::lox_runner::test_runner::run_tests;
+
+#[test]
+fn failures_are_isolated_and_counted() {
+    let name = format!("rlox_test_runner_{}", std::process::id());
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(
+        dir.join("math.lox"),
+        "var calls = 0;
+        print(\"setup\");
+        fun test_first() { calls = calls + 1; assert(calls == 1); }
+        fun test_second() { calls = calls + 1; assert(calls == 1, \"state leaked\"); }
+        fun test_failing() {
+            print(calls);
+            assert(1 == 2,
+                \"one is not two\");
+        }",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("nested/other.lox"),
+        "fun test_error() { missing(); }",
+    )
+    .unwrap();
+    fs::write(dir.join("notes.txt"), "fun test_ignored() {}").unwrap();
+    let mut report = Vec::new();
+    let summary = run_tests(&dir, &mut report).unwrap();
+    let report = String::from_utf8(report).unwrap();
+    assert_eq!((summary.passed, summary.failed), (2, 2), "{}", report);
+    assert!(!summary.success());
+    assert!(report.contains("Assertion failed at line 7:one is not two"));
+    //The top level ran again for the failing test,`calls` starts over.
+    assert!(
+        report.contains("output:\n       setup \n       0\n"),
+        "{}",
+        report
+    );
+    assert!(report.contains("FAIL test_error"));
+    assert!(!report.contains("test_ignored"));
+}
@@ -0,0 +1,118 @@
+use std::{
+    sync::atomic::Ordering,
+    thread,
+    time::{Duration, Instant},
+};
+
+use rlox::{
+    interpreter::{clock::ManualClock, limits::Interrupt, Interpreter},
+    lox_runner::code_to_stblock,
+};
+
+#[test]
+fn a_manual_clock_controls_now_and_sleep() {
+    let clock = ManualClock::new(1700000000.0);
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock(
+        "var start = now();
+        sleep(90);
+        print(now() - start, format_time(now(), \"%Y-%m-%d %H:%M:%S %a\"));",
+    )
+    .unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.set_clock(Box::new(clock.clone()));
+    interpreter.try_interpret(&ast).unwrap();
+    clock.advance(3600.0);
+    interpreter.try_interpret(&ast).unwrap();
+    drop(interpreter);
+    drop(ast);
+    assert_eq!(
+        String::from_utf8(stdout).unwrap(),
+        "90 2023-11-14 22:14:50 Tue \n90 2023-11-14 23:16:20 Tue \n"
+    );
+}
+
+#[test]
+fn clock_follows_a_manual_clock() {
+    let clock = ManualClock::new(50.0);
+    let mut stdout = Vec::new();
+    let ast =
+        code_to_stblock("var start = clock(); sleep(2.5); print(start, clock() - start);").unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.set_clock(Box::new(clock));
+    interpreter.try_interpret(&ast).unwrap();
+    drop(interpreter);
+    drop(ast);
+    assert_eq!(String::from_utf8(stdout).unwrap(), "50 2.5 \n");
+}
+
+#[test]
+fn parse_time_reads_what_format_time_writes() {
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock(
+        "var pattern = \"%d/%m/%Y %H:%M:%S\";
+        var text = format_time(-86401, pattern);
+        print(text, parse_time(text, pattern), parse_time(\"2024-060\", \"%Y-%j\") == parse_time(\"2024-02-29\", \"%Y-%m-%d\"));",
+    )
+    .unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.try_interpret(&ast).unwrap();
+    let invalid = code_to_stblock("parse_time(\"2023-02-29\", \"%Y-%m-%d\");").unwrap();
+    let error = interpreter.try_interpret(&invalid).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "parse_time read \"2023-02-29\",which is not a valid date."
+    );
+    drop(interpreter);
+    drop(ast);
+    drop(invalid);
+    assert_eq!(
+        String::from_utf8(stdout).unwrap(),
+        "30/12/1969 23:59:59 -86401 true \n"
+    );
+}
+
+#[test]
+fn out_of_range_times_are_errors() {
+    let mut stdout = Vec::new();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    let sleep = code_to_stblock("sleep(100000000000000000000);").unwrap();
+    let error = interpreter.try_interpret(&sleep).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "sleep expects a number of seconds,got 100000000000000000000."
+    );
+    let parse = code_to_stblock("parse_time(\"999999999999999999\", \"%Y\");").unwrap();
+    let error = interpreter.try_interpret(&parse).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "parse_time can't read \"999999999999999999\" with the pattern \"%Y\"."
+    );
+}
+
+#[test]
+fn sleep_stops_at_the_timeout_and_on_cancel() {
+    let mut stdout = Vec::new();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    let ast = code_to_stblock("sleep(60);").unwrap();
+    interpreter.set_timeout(Some(Duration::from_millis(50)));
+    let start = Instant::now();
+    let error = interpreter.try_interpret(&ast).unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<Interrupt>(),
+        Some(&Interrupt::Timeout(Duration::from_millis(50)))
+    );
+    interpreter.set_timeout(None);
+    let cancel = interpreter.cancel_handle();
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        cancel.store(true, Ordering::Relaxed);
+    });
+    let error = interpreter.try_interpret(&ast).unwrap_err();
+    canceller.join().unwrap();
+    assert_eq!(
+        error.downcast_ref::<Interrupt>(),
+        Some(&Interrupt::Cancelled)
+    );
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
@@ -0,0 +1,107 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use rlox::{interpreter::Interpreter, lox_runner::code_to_stblock, trace::Tracer};
+
+///Keeps what is written where the test can read it after the run.
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn trace(code: &str, function: Option<&str>) -> String {
+    let out = Shared::default();
+    let mut tracer = Tracer::new(Box::new(out.clone()), code);
+    tracer.set_function(function.map(str::to_string));
+    let mut stdout = Vec::new();
+    let ast = code_to_stblock(code).unwrap();
+    let mut interpreter = Interpreter::new(&mut stdout);
+    interpreter.set_statement_hook(Some(Box::new(tracer)));
+    let _ = interpreter.try_interpret(&ast);
+    let output = String::from_utf8(out.0.borrow().clone()).unwrap();
+    output
+}
+
+const PROGRAM: &str = "fun add(a, b) {
+  var sum = a + b;
+  return sum;
+}
+fun twice(x) {
+  return add(x, x);
+}
+var y = \"a\";
+y = twice(2);";
+
+#[test]
+fn logs_statements_calls_and_variables() {
+    let expected = "[line 1] fun add(a, b) {
+define add = <fn add>
+[line 5] fun twice(x) {
+define twice = <fn twice>
+[line 8] var y = \"a\";
+define y = \"a\"
+[line 9] y = twice(2);
+call twice(2) from line 9
+  define x = 2
+  [line 6] return add(x, x);
+  call add(2, 2) from line 6
+    define a = 2
+    define b = 2
+    [line 2] var sum = a + b;
+    define sum = 4
+    [line 3] return sum;
+  return add -> 4
+return twice -> 4
+assign y = 4
+";
+    assert_eq!(trace(PROGRAM, None), expected);
+}
+
+#[test]
+fn filters_by_function() {
+    let expected = "  call add(2, 2) from line 6
+    define a = 2
+    define b = 2
+    [line 2] var sum = a + b;
+    define sum = 4
+    [line 3] return sum;
+  return add -> 4
+";
+    assert_eq!(trace(PROGRAM, Some("add")), expected);
+}
+
+#[test]
+fn logs_calls_that_fail() {
+    let output = trace("fun fail() { return 1 + nil; }\nfail();", None);
+    assert!(
+        output.ends_with("return fail with an error\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn folded_statements_keep_their_lines() {
+    let code = "var a = 1;
+1 + 2;
+-3;
+true and false;
+print(a);";
+    let expected = "[line 1] var a = 1;
+define a = 1
+[line 2] 1 + 2;
+[line 3] -3;
+[line 4] true and false;
+[line 5] print(a);
+";
+    assert_eq!(trace(code, None), expected);
+}